@@ -0,0 +1,68 @@
+//! Compares decoding a response into the owned model and into the borrowed model.
+//! Run with `cargo bench --bench deserialize`.
+
+use connpass_rs::response::{borrowed, ConnpassResponse};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use serde_json::json;
+
+/// Makes a response like `count(100)` with long HTML descriptions.
+fn response_json(escaped: bool) -> Vec<u8> {
+    // Descriptions from connpass often contain line breaks, which are escaped in JSON and can't be borrowed.
+    let separator = if escaped { "\n" } else { " " };
+    let description = (0..50)
+        .map(|i| format!("<p>Paragraph {} of the description with <a href=\"https://example.com/{}\">a link</a>.</p>", i, i))
+        .collect::<Vec<_>>()
+        .join(separator);
+    let events: Vec<_> = (0..100)
+        .map(|i| {
+            json!({
+                "event_id": 200000 + i,
+                "title": format!("Rust.Tokyo #{}", i),
+                "catch": "The conference for the Rust programming language",
+                "description": description,
+                "event_url": format!("https://rust.connpass.com/event/{}/", 200000 + i),
+                "hash_tag": "rusttokyo",
+                "started_at": "2021-11-27T13:00:00+09:00",
+                "ended_at": "2021-11-27T18:00:00+09:00",
+                "limit": 100,
+                "event_type": "participation",
+                "series": { "id": 4424, "title": "Rust", "url": "https://rust.connpass.com/" },
+                "address": "Online",
+                "place": "YouTube Live",
+                "lat": "35.680959",
+                "lon": "139.767307",
+                "owner_id": 1,
+                "owner_nickname": "yuk1ty",
+                "owner_display_name": "yuk1ty",
+                "accepted": 42,
+                "waiting": 0,
+                "updated_at": "2021-11-01T12:00:00+09:00",
+            })
+        })
+        .collect();
+    serde_json::to_vec(&json!({
+        "results_returned": 100,
+        "results_available": 1000,
+        "results_start": 1,
+        "events": events,
+    }))
+    .unwrap()
+}
+
+fn bench_deserialize(c: &mut Criterion) {
+    for (name, escaped) in [("plain", false), ("escaped", true)] {
+        let bytes = response_json(escaped);
+        let mut group = c.benchmark_group(format!("deserialize/{}", name));
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_function("owned", |b| {
+            b.iter(|| serde_json::from_slice::<ConnpassResponse>(black_box(&bytes)).unwrap())
+        });
+        group.bench_function("borrowed", |b| {
+            b.iter(|| borrowed::ConnpassResponse::from_slice(black_box(&bytes)).unwrap())
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_deserialize);
+criterion_main!(benches);
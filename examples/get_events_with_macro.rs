@@ -0,0 +1,21 @@
+use connpass_rs::{client::ConnpassClient, connpass_query};
+
+#[tokio::main]
+async fn main() {
+    // The same query as `get_events_with_various_queries`, built declaratively.
+    // `count` and `format` written as literals are validated at compile time.
+    let query = connpass_query! {
+        keyword_or: ["Python", "機械学習"],
+        ym: [202110, 202111],
+        order: Newer,
+        count: 15,
+    };
+    if let Ok(query) = query {
+        let client = ConnpassClient::new();
+        let res = client.send_request(query).await;
+        match res {
+            Ok(r) => println!("{:?}", r),
+            Err(err) => eprintln!("{:?}", err),
+        }
+    }
+}
@@ -1,15 +1,12 @@
-use connpass_rs::{client::ConnpassClient, query::builder::QueryBuilder};
+use connpass_rs::{client::ConnpassClient, id::EventId};
 
 #[tokio::main]
 async fn main() {
     // fetch https://rust.connpass.com/event/228732/
-    let query = QueryBuilder::begin().event_id(228732).build();
-    if let Ok(query) = query {
-        let client = ConnpassClient::new();
-        let res = client.send_request(query).await;
-        match res {
-            Ok(r) => println!("{:?}", r),
-            Err(err) => eprintln!("{:?}", err),
-        }
+    let client = ConnpassClient::new();
+    match client.get_event(EventId::new(228732)).await {
+        Ok(Some(event)) => println!("{:?}", event),
+        Ok(None) => eprintln!("The event is not found"),
+        Err(err) => eprintln!("{:?}", err),
     }
 }
@@ -0,0 +1,236 @@
+//! Command line arguments of `connpass`.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use connpass_rs::{
+    errors::ConnpassResult,
+    id::{EventId, SeriesId},
+    query::{builder::QueryBuilder, types::OrderOption, Query},
+    url::EventUrl,
+};
+
+#[derive(Parser)]
+#[command(name = "connpass", version, about = "Searches events on connpass.com")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Searches events with the given conditions.
+    Search(Box<SearchArgs>),
+    /// Shows the details of a single event.
+    Event(EventArgs),
+}
+
+#[derive(Args)]
+pub struct SearchArgs {
+    /// Event ids. Can be specified multiple times.
+    #[arg(long)]
+    pub event_id: Vec<EventId>,
+    /// Keywords that all must match (AND). Can be specified multiple times.
+    #[arg(long)]
+    pub keyword: Vec<String>,
+    /// Keywords that any must match (OR). Can be specified multiple times.
+    #[arg(long)]
+    pub keyword_or: Vec<String>,
+    /// Year and month of the event, e.g. 202110. Can be specified multiple times.
+    #[arg(long)]
+    pub ym: Vec<u32>,
+    /// Date of the event, e.g. 20211012. Can be specified multiple times.
+    #[arg(long)]
+    pub ymd: Vec<u32>,
+    /// Nicknames of participants. Can be specified multiple times.
+    #[arg(long)]
+    pub nickname: Vec<String>,
+    /// Nicknames of owners. Can be specified multiple times.
+    #[arg(long)]
+    pub owner_nickname: Vec<String>,
+    /// Series (group) ids. Can be specified multiple times.
+    #[arg(long)]
+    pub series_id: Vec<SeriesId>,
+    /// Position of the first result, starting from 1.
+    #[arg(long)]
+    pub start: Option<u32>,
+    /// Order of the results.
+    #[arg(long, value_enum)]
+    pub order: Option<Order>,
+    /// Number of results per request, from 1 to 100.
+    #[arg(long)]
+    pub count: Option<u8>,
+    /// Format of the responses. Only "json" is accepted.
+    #[arg(long)]
+    pub format: Option<String>,
+    /// Fetches all the pages instead of the first one.
+    #[arg(long)]
+    pub all: bool,
+    /// Output format.
+    #[arg(long, short, value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+}
+
+impl SearchArgs {
+    pub fn to_query(&self) -> ConnpassResult<Query> {
+        let mut builder = QueryBuilder::begin();
+        if !self.event_id.is_empty() {
+            builder = builder.event_ids(self.event_id.clone());
+        }
+        if !self.keyword.is_empty() {
+            builder = builder.keywords(self.keyword.clone());
+        }
+        if !self.keyword_or.is_empty() {
+            builder = builder.keywords_or(self.keyword_or.clone());
+        }
+        if !self.ym.is_empty() {
+            builder = builder.yms(self.ym.clone());
+        }
+        if !self.ymd.is_empty() {
+            builder = builder.ymds(self.ymd.clone());
+        }
+        if !self.nickname.is_empty() {
+            builder = builder.nicknames(self.nickname.clone());
+        }
+        if !self.owner_nickname.is_empty() {
+            builder = builder.owner_nicknames(self.owner_nickname.clone());
+        }
+        if !self.series_id.is_empty() {
+            builder = builder.series_ids(self.series_id.clone());
+        }
+        if let Some(start) = self.start {
+            builder = builder.start(start);
+        }
+        if let Some(order) = self.order {
+            builder = builder.order(order.into());
+        }
+        if let Some(count) = self.count {
+            builder = builder.count(count);
+        }
+        if let Some(format) = &self.format {
+            builder = builder.format(format);
+        }
+        builder.build()
+    }
+}
+
+#[derive(Args)]
+pub struct EventArgs {
+    /// Event id or URL, e.g. 228732 or https://rust.connpass.com/event/228732/
+    #[arg(value_name = "ID|URL", value_parser = parse_event_id)]
+    pub event_id: EventId,
+}
+
+/// Accepts either an event id or an event URL.
+fn parse_event_id(s: &str) -> Result<EventId, String> {
+    s.parse()
+        .or_else(|_| s.parse::<EventUrl>().map(|url| url.event_id()))
+        .map_err(|_| format!("`{}` is neither an event id nor an event URL", s))
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum Order {
+    LastModified,
+    EventDate,
+    Newer,
+}
+
+impl From<Order> for OrderOption {
+    fn from(order: Order) -> Self {
+        match order {
+            Order::LastModified => OrderOption::LastModifiedDate,
+            Order::EventDate => OrderOption::EventDate,
+            Order::Newer => OrderOption::Newer,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+    Ics,
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+    use connpass_rs::{
+        id::SeriesId,
+        query::{builder::QueryBuilder, types::OrderOption},
+    };
+
+    use super::{parse_event_id, Cli, Command, EventId, OutputFormat, SearchArgs};
+
+    fn search_args(args: &[&str]) -> SearchArgs {
+        let args = ["connpass", "search"].iter().chain(args);
+        match Cli::try_parse_from(args).unwrap().command {
+            Command::Search(args) => *args,
+            Command::Event(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_search_args_to_query() {
+        let args = search_args(&[
+            "--keyword",
+            "Rust",
+            "--keyword",
+            "Tokyo",
+            "--keyword-or",
+            "Python",
+            "--ym",
+            "202110",
+            "--series-id",
+            "4424",
+            "--order",
+            "newer",
+            "--count",
+            "100",
+            "--format",
+            "json",
+            "--all",
+            "-o",
+            "csv",
+        ]);
+        assert!(args.all);
+        assert_eq!(args.output, OutputFormat::Csv);
+        let expected = QueryBuilder::begin()
+            .keywords(vec!["Rust".to_string(), "Tokyo".to_string()])
+            .keyword_or("Python")
+            .ym(202110)
+            .series_id(SeriesId::new(4424))
+            .order(OrderOption::Newer)
+            .count(100)
+            .format("json")
+            .build()
+            .unwrap();
+        assert_eq!(args.to_query().unwrap(), expected);
+
+        let args = search_args(&["--keyword", "Rust"]);
+        assert!(!args.all);
+        assert_eq!(args.output, OutputFormat::Table);
+    }
+
+    #[test]
+    fn test_search_args_to_invalid_query() {
+        assert!(search_args(&["--format", "xml"]).to_query().is_err());
+        assert!(search_args(&["--count", "0"]).to_query().is_err());
+        assert!(Cli::try_parse_from(["connpass", "search", "--order", "older"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_event_id() {
+        assert_eq!(parse_event_id("228732"), Ok(EventId::new(228732)));
+        assert_eq!(
+            parse_event_id("https://rust.connpass.com/event/228732/"),
+            Ok(EventId::new(228732))
+        );
+        assert_eq!(
+            parse_event_id("https://connpass.com/event/228732/participation/"),
+            Ok(EventId::new(228732))
+        );
+        assert!(parse_event_id("https://rust.connpass.com/").is_err());
+        assert!(parse_event_id("https://example.com/event/228732/").is_err());
+        assert!(parse_event_id("rust").is_err());
+    }
+}
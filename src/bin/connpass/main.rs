@@ -0,0 +1,97 @@
+//! `connpass` command for looking up events on connpass.com from a terminal.
+//! This can be built with `cargo build --features cli`.
+
+use std::{fmt, io, process};
+
+use clap::Parser;
+use connpass_rs::{client::ConnpassClient, errors::ConnpassCliError, id::EventId};
+use futures::{stream, StreamExt};
+
+use crate::{
+    args::{Cli, Command, EventArgs, SearchArgs},
+    output::{print_detail, Printer},
+};
+
+mod args;
+mod output;
+
+/// Exit code for the errors not categorised below, e.g. failing to write the output.
+const EXIT_FAILURE: i32 = 1;
+/// Exit code for invalid arguments, which is the same as clap's.
+const EXIT_INVALID_ARGS: i32 = 2;
+/// Exit code for the event not found.
+const EXIT_NOT_FOUND: i32 = 3;
+/// Exit code for errors around HTTP connection, e.g. transport errors or unexpected status codes.
+const EXIT_HTTP: i32 = 4;
+
+enum Failure {
+    NotFound(EventId),
+    Connpass(ConnpassCliError),
+}
+
+impl From<ConnpassCliError> for Failure {
+    fn from(err: ConnpassCliError) -> Self {
+        Failure::Connpass(err)
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::NotFound(id) => write!(f, "event {} is not found", id),
+            Failure::Connpass(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Failure {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Failure::NotFound(_) => EXIT_NOT_FOUND,
+            Failure::Connpass(ConnpassCliError::Validation(_)) => EXIT_INVALID_ARGS,
+            Failure::Connpass(ConnpassCliError::HttpResponse(_)) => EXIT_HTTP,
+            Failure::Connpass(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let client = ConnpassClient::new();
+    let result = match cli.command {
+        Command::Search(args) => search(&client, args).await,
+        Command::Event(args) => event(&client, args).await,
+    };
+
+    if let Err(failure) = result {
+        eprintln!("error: {}", failure);
+        process::exit(failure.exit_code());
+    }
+}
+
+async fn event(client: &ConnpassClient, args: EventArgs) -> Result<(), Failure> {
+    let event = client
+        .get_event(args.event_id)
+        .await?
+        .ok_or(Failure::NotFound(args.event_id))?;
+    print_detail(&mut io::stdout().lock(), &event)?;
+    Ok(())
+}
+
+async fn search(client: &ConnpassClient, args: Box<SearchArgs>) -> Result<(), Failure> {
+    let query = args.to_query()?;
+    let mut pages = if args.all {
+        client.pages(query).boxed()
+    } else {
+        let client = client.clone();
+        stream::once(async move { client.send_request(query).await }).boxed()
+    };
+
+    let mut printer = Printer::new(args.output, io::stdout().lock())?;
+    while let Some(page) = pages.next().await {
+        printer.print(page?.into_events())?;
+    }
+    printer.finish()?;
+    Ok(())
+}
@@ -0,0 +1,261 @@
+//! Prints events in the format specified by `--output`.
+//! Events are printed page by page, except for ICS which needs the whole calendar at once.
+
+use std::io::Write;
+
+use connpass_rs::{
+    errors::{ConnpassCliError, ConnpassResult, ExportError},
+    export::{Column, CsvWriter, EventWriter},
+    ics,
+    response::{Capacity, Event},
+};
+
+use crate::args::OutputFormat;
+
+pub enum Printer<W: Write> {
+    Table { out: W, header_written: bool },
+    Json { out: W, count: usize },
+    Csv(Box<CsvWriter<W>>),
+    Ics { out: W, events: Vec<Event> },
+}
+
+impl<W: Write> Printer<W> {
+    pub fn new(format: OutputFormat, out: W) -> ConnpassResult<Self> {
+        Ok(match format {
+            OutputFormat::Table => Printer::Table {
+                out,
+                header_written: false,
+            },
+            OutputFormat::Json => Printer::Json { out, count: 0 },
+            OutputFormat::Csv => Printer::Csv(Box::new(CsvWriter::new(out, Column::ALL.to_vec())?)),
+            OutputFormat::Ics => Printer::Ics {
+                out,
+                events: Vec::new(),
+            },
+        })
+    }
+
+    pub fn print(&mut self, events: Vec<Event>) -> ConnpassResult<()> {
+        match self {
+            Printer::Table {
+                out,
+                header_written,
+            } => {
+                if !*header_written {
+                    writeln!(
+                        out,
+                        "{:<10}  {:<16}  {:<9}  TITLE",
+                        "EVENT_ID", "STARTED_AT", "SEATS"
+                    )
+                    .map_err(io_error)?;
+                    *header_written = true;
+                }
+                for event in &events {
+                    writeln!(
+                        out,
+                        "{:<10}  {:<16}  {:<9}  {}",
+                        event.id(),
+                        started_at(event),
+                        seats(event),
+                        event.title().unwrap_or_default()
+                    )
+                    .map_err(io_error)?;
+                }
+                Ok(())
+            }
+            Printer::Json { out, count } => {
+                for event in &events {
+                    out.write_all(if *count == 0 { b"[\n" } else { b",\n" })
+                        .map_err(io_error)?;
+                    serde_json::to_writer(&mut *out, event).map_err(|err| {
+                        ConnpassCliError::Export(ExportError::Json(format!("{}", err)))
+                    })?;
+                    *count += 1;
+                }
+                Ok(())
+            }
+            Printer::Csv(writer) => {
+                for event in &events {
+                    writer.write_event(event)?;
+                }
+                Ok(())
+            }
+            Printer::Ics { events: all, .. } => {
+                all.extend(events);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn finish(self) -> ConnpassResult<()> {
+        match self {
+            Printer::Table { mut out, .. } => out.flush().map_err(io_error),
+            Printer::Json { mut out, count } => {
+                out.write_all(if count == 0 { b"[]\n" } else { b"\n]\n" })
+                    .map_err(io_error)?;
+                out.flush().map_err(io_error)
+            }
+            Printer::Csv(mut writer) => writer.flush(),
+            Printer::Ics { mut out, events } => {
+                out.write_all(ics::vcalendar(&events).as_bytes())
+                    .map_err(io_error)?;
+                out.flush().map_err(io_error)
+            }
+        }
+    }
+}
+
+/// Prints the details of a single event.
+pub fn print_detail<W: Write>(out: &mut W, event: &Event) -> ConnpassResult<()> {
+    let mut lines = vec![event.title().unwrap_or_default().to_string()];
+    if let Some(catch) = event.catch().filter(|c| !c.is_empty()) {
+        lines.push(catch.to_string());
+    }
+    lines.push(String::new());
+
+    let mut field = |name: &str, value: Option<String>| {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            lines.push(format!("{:<10}{}", name, value));
+        }
+    };
+    field("ID", Some(event.id().to_string()));
+    field("URL", event.event_url().map(str::to_string));
+    field(
+        "Date",
+        event.started_at().map(|started_at| match event.ended_at() {
+            Some(ended_at) => format!("{} - {}", started_at, ended_at),
+            None => started_at.to_string(),
+        }),
+    );
+    field("Place", event.place().map(str::to_string));
+    field("Address", event.address().map(str::to_string));
+    field("Map", map_url(event));
+    field("Seats", Some(seats(event)));
+    field(
+        "Waiting",
+        event.waiting().filter(|w| *w > 0).map(|w| w.to_string()),
+    );
+    field(
+        "Series",
+        event.series().map(|s| {
+            let title = s.title().unwrap_or_default();
+            match s.url() {
+                Some(url) => format!("{} ({})", title, url),
+                None => title.to_string(),
+            }
+        }),
+    );
+    field("Owner", owner(event));
+    field("Hashtag", event.hash_tag().map(|h| format!("#{}", h)));
+    field("Updated", event.updated_at().map(str::to_string));
+
+    if let Some(description) = event.description_text() {
+        lines.push(String::new());
+        lines.push(description);
+    }
+
+    writeln!(out, "{}", lines.join("\n")).map_err(io_error)
+}
+
+fn map_url(event: &Event) -> Option<String> {
+    match (event.lat(), event.lon()) {
+        (Some(lat), Some(lon)) if !lat.is_empty() && !lon.is_empty() => Some(format!(
+            "https://www.google.com/maps/search/?api=1&query={},{}",
+            lat, lon
+        )),
+        _ => None,
+    }
+}
+
+fn owner(event: &Event) -> Option<String> {
+    match (event.owner_display_name(), event.owner_nickname()) {
+        (Some(name), Some(nickname)) => Some(format!("{} (@{})", name, nickname)),
+        (Some(name), None) => Some(name.to_string()),
+        (None, Some(nickname)) => Some(format!("@{}", nickname)),
+        (None, None) => None,
+    }
+}
+
+/// Shortens `2021-11-12T19:30:00+09:00` into `2021-11-12 19:30`.
+fn started_at(event: &Event) -> String {
+    match event.started_at() {
+        Some(s) => s.get(..16).unwrap_or(s).replacen('T', " ", 1),
+        None => "-".to_string(),
+    }
+}
+
+fn seats(event: &Event) -> String {
+    let accepted = event.accepted().unwrap_or(0);
+    match event.capacity() {
+        Capacity::Unlimited => format!("{}/-", accepted),
+        Capacity::Limited(limit) => format!("{}/{}", accepted, limit),
+    }
+}
+
+fn io_error(err: std::io::Error) -> ConnpassCliError {
+    ConnpassCliError::Export(ExportError::Io(err))
+}
+
+#[cfg(test)]
+mod test {
+    use connpass_rs::response::Event;
+    use serde_json::json;
+
+    use crate::args::OutputFormat;
+
+    use super::Printer;
+
+    fn event(id: u32, title: &str) -> Event {
+        serde_json::from_value(json!({
+            "event_id": id,
+            "title": title,
+            "started_at": "2021-11-12T19:30:00+09:00",
+            "limit": 30,
+            "accepted": 10,
+        }))
+        .unwrap()
+    }
+
+    fn print(format: OutputFormat, pages: Vec<Vec<Event>>) -> String {
+        let mut out = Vec::new();
+        let mut printer = Printer::new(format, &mut out).unwrap();
+        for events in pages {
+            printer.print(events).unwrap();
+        }
+        printer.finish().unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_print_table() {
+        let pages = vec![vec![event(1, "Rust.Tokyo")], vec![event(2, "Rust.Osaka")]];
+        assert_eq!(
+            print(OutputFormat::Table, pages),
+            "EVENT_ID    STARTED_AT        SEATS      TITLE\n\
+             1           2021-11-12 19:30  10/30      Rust.Tokyo\n\
+             2           2021-11-12 19:30  10/30      Rust.Osaka\n"
+        );
+    }
+
+    #[test]
+    fn test_print_json() {
+        let pages = vec![vec![event(1, "Rust.Tokyo")], vec![event(2, "Rust.Osaka")]];
+        let printed: serde_json::Value =
+            serde_json::from_str(&print(OutputFormat::Json, pages)).unwrap();
+        let ids: Vec<&serde_json::Value> = printed
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| &e["event_id"])
+            .collect();
+        assert_eq!(ids, vec![&json!(1), &json!(2)]);
+        assert_eq!(print(OutputFormat::Json, vec![]), "[]\n");
+    }
+
+    #[test]
+    fn test_print_ics() {
+        let printed = print(OutputFormat::Ics, vec![vec![event(1, "Rust.Tokyo")]]);
+        assert!(printed.starts_with("BEGIN:VCALENDAR"));
+        assert!(printed.contains("SUMMARY:Rust.Tokyo"));
+    }
+}
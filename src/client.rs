@@ -2,16 +2,31 @@
 //! This module provides non-blocking API (on tokio runtime) normally, but when `blocking` feature is enabled, additionally start to provide blocking API.
 //! These clients are internally using `reqwest` crate.
 
+#[cfg(feature = "config")]
+use std::sync::Arc;
+use std::{collections::HashSet, time::Duration};
+
+use bytes::Bytes;
+use futures::{stream, Stream};
 use once_cell::sync::Lazy;
 use reqwest::{header::USER_AGENT, Client, Response, StatusCode};
 
+#[cfg(feature = "config")]
+use crate::{
+    config::{ClientSettings, Config},
+    errors::ConfigError,
+};
 use crate::{
     errors::{ConnpassCliError, ConnpassResult, HttpResponseError},
-    query::Query,
-    response::ConnpassResponse,
+    id::{EventId, SeriesId},
+    query::{builder::QueryBuilder, validator::Policies, Query},
+    resolver::{sample_query, unresolved, SeriesResolver, MAX_SAMPLED_PAGES},
+    response::{ConnpassResponse, Event, EventLookup},
 };
 
-const BASE_URL: &str = "https://connpass.com/api/v1/event/";
+pub(crate) const BASE_URL: &str = "https://connpass.com/api/v1/event/";
+/// How many ids are looked up in a request by `get_events`, which is the maximum of `count`.
+const MAX_IDS_PER_REQUEST: usize = 100;
 static CRATE_USER_AGENT: Lazy<String> = Lazy::new(|| {
     format!(
         "connpass-rs/{} (+https://github.com/yuk1ty/connpass-rs)",
@@ -23,12 +38,24 @@ static CRATE_USER_AGENT: Lazy<String> = Lazy::new(|| {
 #[derive(Clone)]
 pub struct ConnpassClient {
     client: Client,
+    resolver: SeriesResolver,
+    lenient: bool,
+    policies: Policies,
+    timeout: Option<Duration>,
+    #[cfg(feature = "config")]
+    config: Arc<Config>,
 }
 
 impl Default for ConnpassClient {
     fn default() -> Self {
         ConnpassClient {
             client: Client::new(),
+            resolver: SeriesResolver::new(),
+            lenient: false,
+            policies: Policies::new(),
+            timeout: None,
+            #[cfg(feature = "config")]
+            config: Arc::default(),
         }
     }
 }
@@ -40,7 +67,74 @@ impl ConnpassClient {
 
     /// Initializes client with your own client.
     pub fn with_client(client: Client) -> Self {
-        ConnpassClient { client }
+        ConnpassClient {
+            client,
+            resolver: SeriesResolver::new(),
+            lenient: false,
+            policies: Policies::new(),
+            timeout: None,
+            #[cfg(feature = "config")]
+            config: Arc::default(),
+        }
+    }
+
+    /// Replaces the resolver for group subdomains, e.g. to share its cache among clients.
+    pub fn with_resolver(mut self, resolver: SeriesResolver) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    /// Decodes responses in the lenient mode, which tolerates changes of the API schema.
+    /// See `response::lenient` module for details.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Checks every query against the policies before sending it, including the ones made by the client itself, e.g. in `get_event`.
+    /// Group subdomains are checked after they are resolved into series ids, and the queries sampling events to resolve them are not checked.
+    pub fn with_policies(mut self, policies: Policies) -> Self {
+        self.policies = policies;
+        self
+    }
+
+    /// Sets the timeout of each request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Applies the client settings loaded from a config. The unset ones are kept as they are.
+    #[cfg(feature = "config")]
+    pub fn with_settings(mut self, settings: &ClientSettings) -> Self {
+        if let Some(lenient) = settings.lenient() {
+            self.lenient = lenient;
+        }
+        if let Some(timeout) = settings.timeout() {
+            self.timeout = Some(timeout);
+        }
+        self
+    }
+
+    /// Applies the settings in `[client]` of the config, and keeps its profiles for `run_profile`.
+    #[cfg(feature = "config")]
+    pub fn with_config(self, config: Config) -> Self {
+        let mut client = self.with_settings(config.client());
+        client.config = Arc::new(config);
+        client
+    }
+
+    /// Sends the query of the profile in the config set by `with_config`, applying the client settings of the profile.
+    #[cfg(feature = "config")]
+    pub async fn run_profile(&self, name: &str) -> ConnpassResult<ConnpassResponse> {
+        let profile = self
+            .config
+            .profile(name)
+            .ok_or_else(|| unknown_profile(name))?;
+        self.clone()
+            .with_settings(profile.client())
+            .send_request(profile.query().clone())
+            .await
     }
 
     /// Sends requests and gets response from API.
@@ -52,12 +146,12 @@ impl ConnpassClient {
     ///
     /// # Example:
     /// ```
-    /// use connpass_rs::{client::ConnpassClient, query::builder::QueryBuilder};
+    /// use connpass_rs::{client::ConnpassClient, id::EventId, query::builder::QueryBuilder};
     ///
     /// #[tokio::main]
     /// async fn main() {
     ///     // fetch https://rust.connpass.com/event/228732/
-    ///     let query = QueryBuilder::begin().event_id(228732).build();
+    ///     let query = QueryBuilder::begin().event_id(EventId::new(228732)).build();
     ///     if let Ok(query) = query {
     ///         let client = ConnpassClient::new();
     ///         let res = client.send_request(query).await;
@@ -68,66 +162,279 @@ impl ConnpassClient {
     ///     }
     /// }
     /// ```
-    pub async fn send_request(self, query: Query) -> ConnpassResult<ConnpassResponse> {
-        let response = self
+    pub async fn send_request(&self, mut query: Query) -> ConnpassResult<ConnpassResponse> {
+        self.resolve(&mut query).await?;
+        self.send(query).await
+    }
+
+    /// Sends the request like `send_request`, but returns the body as it is.
+    /// This is for decoding the body into `response::borrowed::ConnpassResponse`, which borrows strings from it.
+    pub async fn send_request_bytes(&self, mut query: Query) -> ConnpassResult<Bytes> {
+        self.resolve(&mut query).await?;
+        self.send_raw(query)
+            .await?
+            .bytes()
+            .await
+            .map_err(|err| ConnpassCliError::HttpResponse(HttpResponseError::ReqwestError(err)))
+    }
+
+    /// Fetches the event of the id, or returns `None` if it doesn't exist.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// use connpass_rs::{client::ConnpassClient, id::EventId};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // fetch https://rust.connpass.com/event/228732/
+    ///     let client = ConnpassClient::new();
+    ///     match client.get_event(EventId::new(228732)).await {
+    ///         Ok(Some(event)) => println!("{:?}", event),
+    ///         Ok(None) => eprintln!("not found"),
+    ///         Err(err) => eprintln!("{:?}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_event(&self, event_id: EventId) -> ConnpassResult<Option<Event>> {
+        let res = self.send(event_query(event_id)?).await?;
+        Ok(find_event(res, event_id))
+    }
+
+    /// Fetches the events of the ids. The ids are looked up 100 by 100,
+    /// and the ones not found are reported by `EventLookup::missing`.
+    pub async fn get_events(&self, event_ids: &[EventId]) -> ConnpassResult<EventLookup> {
+        let mut found = Vec::new();
+        for query in lookup_queries(event_ids)? {
+            found.extend(self.send(query).await?.into_events());
+        }
+        Ok(EventLookup::new(event_ids, found))
+    }
+
+    /// Replaces the group subdomains in the query with their series ids.
+    async fn resolve(&self, query: &mut Query) -> ConnpassResult<()> {
+        for subdomain in query.take_group_subdomains() {
+            let series_id = self.resolve_series_id(&subdomain).await?;
+            query.push_series_id(series_id);
+        }
+        Ok(())
+    }
+
+    /// Resolves the group subdomain (e.g. `rust` of `rust.connpass.com`) into its series id.
+    /// The result is cached, so this sends requests only for the first time for each subdomain.
+    /// See `resolver` module for how it's resolved.
+    pub async fn resolve_series_id(&self, subdomain: &str) -> ConnpassResult<SeriesId> {
+        if let Some(series_id) = self.resolver.cached(subdomain) {
+            return Ok(series_id);
+        }
+        for page in 0..MAX_SAMPLED_PAGES {
+            // The sampling queries are internal, so the policies are not applied to them.
+            let res = self
+                .decode(self.request(sample_query(subdomain, page)?).await?)
+                .await?;
+            if let Some(series_id) = self.resolver.find_in(subdomain, &res) {
+                return Ok(series_id);
+            }
+            if next_start(&res).is_none() {
+                break;
+            }
+        }
+        Err(unresolved(subdomain))
+    }
+
+    async fn send(&self, query: Query) -> ConnpassResult<ConnpassResponse> {
+        let response = self.send_raw(query).await?;
+        self.decode(response).await
+    }
+
+    async fn decode(&self, response: Response) -> ConnpassResult<ConnpassResponse> {
+        if self.lenient {
+            let bytes = response.bytes().await.map_err(|err| {
+                ConnpassCliError::HttpResponse(HttpResponseError::ReqwestError(err))
+            })?;
+            ConnpassResponse::from_slice_lenient(&bytes)
+        } else {
+            response
+                .json::<ConnpassResponse>()
+                .await
+                .map_err(json_decode_error)
+        }
+    }
+
+    async fn send_raw(&self, query: Query) -> ConnpassResult<Response> {
+        self.policies.validate(&query)?;
+        self.request(query).await
+    }
+
+    /// Sends the query without checking the policies, which is only for the queries made for resolving group subdomains.
+    async fn request(&self, query: Query) -> ConnpassResult<Response> {
+        let mut request = self
             .client
             .get(BASE_URL)
             .header(USER_AGENT, CRATE_USER_AGENT.as_str())
-            .query(&query.make_reqwest_query())
+            .query(&query.make_reqwest_query());
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        let response = request
             .send()
             .await
             .map_err(|err| ConnpassCliError::HttpResponse(HttpResponseError::ReqwestError(err)))?;
-        self.handler(response).await
+        check_status(response.status())?;
+        Ok(response)
     }
 
-    async fn handler(&self, res: Response) -> ConnpassResult<ConnpassResponse> {
-        dbg!("response = {}", &res);
-        match res.status() {
-            StatusCode::OK => res.json::<ConnpassResponse>().await.map_err(|err| {
-                ConnpassCliError::HttpResponse(HttpResponseError::JsonDecode(format!("{}", err)))
-            }),
-            StatusCode::FORBIDDEN => {
-                Err(ConnpassCliError::HttpResponse(HttpResponseError::Forbidden))
+    /// Sends requests page by page, following `results_start` and `results_returned` of each response.
+    /// The first page starts at `start` in the query (or the top if not set), and the stream ends after the last page.
+    /// If a request fails, the error is yielded and the stream ends.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// use connpass_rs::{client::ConnpassClient, query::builder::QueryBuilder};
+    /// use futures::StreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let query = QueryBuilder::begin().keyword("Rust").count(100).build().unwrap();
+    ///     let client = ConnpassClient::new();
+    ///     let mut pages = Box::pin(client.pages(query));
+    ///     while let Some(page) = pages.next().await {
+    ///         match page {
+    ///             Ok(r) => println!("{:?}", r),
+    ///             Err(err) => eprintln!("{:?}", err),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn pages(&self, query: Query) -> impl Stream<Item = ConnpassResult<ConnpassResponse>> {
+        stream::unfold(Some((self.clone(), query)), |state| async move {
+            let (client, query) = state?;
+            match client.send_request(query.clone()).await {
+                Ok(res) => {
+                    let next = next_start(&res).map(|start| (client, query.with_start(start)));
+                    Some((Ok(res), next))
+                }
+                Err(err) => Some((Err(err), None)),
             }
-            StatusCode::INTERNAL_SERVER_ERROR => Err(ConnpassCliError::HttpResponse(
-                HttpResponseError::InternalServerError,
-            )),
-            StatusCode::SERVICE_UNAVAILABLE => Err(ConnpassCliError::HttpResponse(
-                HttpResponseError::ServiceUnavailable,
-            )),
-            s => Err(ConnpassCliError::HttpResponse(HttpResponseError::Various(
-                format!("Unexpected response received: {:?} (status code)", s),
-            ))),
-        }
+        })
+    }
+}
+
+/// Maps the status codes other than 200 OK into errors.
+fn check_status(status: StatusCode) -> ConnpassResult<()> {
+    match status {
+        StatusCode::OK => Ok(()),
+        StatusCode::FORBIDDEN => Err(ConnpassCliError::HttpResponse(HttpResponseError::Forbidden)),
+        StatusCode::INTERNAL_SERVER_ERROR => Err(ConnpassCliError::HttpResponse(
+            HttpResponseError::InternalServerError,
+        )),
+        StatusCode::SERVICE_UNAVAILABLE => Err(ConnpassCliError::HttpResponse(
+            HttpResponseError::ServiceUnavailable,
+        )),
+        s => Err(ConnpassCliError::HttpResponse(HttpResponseError::Various(
+            format!("Unexpected response received: {:?} (status code)", s),
+        ))),
+    }
+}
+
+fn json_decode_error(err: reqwest::Error) -> ConnpassCliError {
+    ConnpassCliError::HttpResponse(HttpResponseError::JsonDecode(format!("{}", err)))
+}
+
+fn event_query(event_id: EventId) -> ConnpassResult<Query> {
+    QueryBuilder::begin().event_id(event_id).build()
+}
+
+fn find_event(res: ConnpassResponse, event_id: EventId) -> Option<Event> {
+    res.into_events().into_iter().find(|e| e.id() == event_id)
+}
+
+/// Makes the queries for looking up the events of the ids, without duplicated ids.
+fn lookup_queries(event_ids: &[EventId]) -> ConnpassResult<Vec<Query>> {
+    let mut seen = HashSet::new();
+    let unique: Vec<EventId> = event_ids
+        .iter()
+        .copied()
+        .filter(|id| seen.insert(*id))
+        .collect();
+    unique
+        .chunks(MAX_IDS_PER_REQUEST)
+        .map(|ids| {
+            QueryBuilder::begin()
+                .event_ids(ids.to_vec())
+                .count(ids.len() as u8)
+                .build()
+        })
+        .collect()
+}
+
+#[cfg(feature = "config")]
+fn unknown_profile(name: &str) -> ConnpassCliError {
+    ConnpassCliError::Config(ConfigError::UnknownProfile(name.to_string()))
+}
+
+/// Returns `start` of the next page, or `None` if the response is the last page.
+fn next_start(res: &ConnpassResponse) -> Option<u32> {
+    let next = res.results_start() + res.results_returned();
+    if res.results_returned() == 0 || next > res.results_available() {
+        None
+    } else {
+        Some(next)
     }
 }
 
 /// The client using blokcing. This one capitalizes on `reqwest::blocking` API.
 #[cfg(feature = "blocking")]
 pub mod blocking {
+    #[cfg(feature = "config")]
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use bytes::Bytes;
     use reqwest::{
         blocking::{Client, Response},
         header::USER_AGENT,
-        StatusCode,
     };
 
+    #[cfg(feature = "config")]
+    use crate::config::{ClientSettings, Config};
+
     use crate::{
         errors::{ConnpassCliError, ConnpassResult, HttpResponseError},
-        query::Query,
-        response::ConnpassResponse,
+        id::{EventId, SeriesId},
+        query::{validator::Policies, Query},
+        resolver::{sample_query, unresolved, SeriesResolver, MAX_SAMPLED_PAGES},
+        response::{ConnpassResponse, Event, EventLookup},
     };
 
-    use super::{BASE_URL, CRATE_USER_AGENT};
+    #[cfg(feature = "config")]
+    use super::unknown_profile;
+    use super::{
+        check_status, event_query, find_event, json_decode_error, lookup_queries, next_start,
+        BASE_URL, CRATE_USER_AGENT,
+    };
 
     /// Blocking API client for accessing and fetching data from connpass.com
+    #[derive(Clone)]
     pub struct ConnpassClient {
         client: Client,
+        resolver: SeriesResolver,
+        lenient: bool,
+        policies: Policies,
+        timeout: Option<Duration>,
+        #[cfg(feature = "config")]
+        config: Arc<Config>,
     }
 
     impl Default for ConnpassClient {
         fn default() -> Self {
             ConnpassClient {
                 client: Client::new(),
+                resolver: SeriesResolver::new(),
+                lenient: false,
+                policies: Policies::new(),
+                timeout: None,
+                #[cfg(feature = "config")]
+                config: Arc::default(),
             }
         }
     }
@@ -139,7 +446,74 @@ pub mod blocking {
 
         /// Initializes client with your own client.
         pub fn with_client(client: Client) -> Self {
-            ConnpassClient { client }
+            ConnpassClient {
+                client,
+                resolver: SeriesResolver::new(),
+                lenient: false,
+                policies: Policies::new(),
+                timeout: None,
+                #[cfg(feature = "config")]
+                config: Arc::default(),
+            }
+        }
+
+        /// Replaces the resolver for group subdomains, e.g. to share its cache among clients.
+        pub fn with_resolver(mut self, resolver: SeriesResolver) -> Self {
+            self.resolver = resolver;
+            self
+        }
+
+        /// Decodes responses in the lenient mode, which tolerates changes of the API schema.
+        /// See `response::lenient` module for details.
+        pub fn lenient(mut self, lenient: bool) -> Self {
+            self.lenient = lenient;
+            self
+        }
+
+        /// Checks every query against the policies before sending it, including the ones made by the client itself, e.g. in `get_event`.
+        /// Group subdomains are checked after they are resolved into series ids, and the queries sampling events to resolve them are not checked.
+        pub fn with_policies(mut self, policies: Policies) -> Self {
+            self.policies = policies;
+            self
+        }
+
+        /// Sets the timeout of each request.
+        pub fn timeout(mut self, timeout: Duration) -> Self {
+            self.timeout = Some(timeout);
+            self
+        }
+
+        /// Applies the client settings loaded from a config. The unset ones are kept as they are.
+        #[cfg(feature = "config")]
+        pub fn with_settings(mut self, settings: &ClientSettings) -> Self {
+            if let Some(lenient) = settings.lenient() {
+                self.lenient = lenient;
+            }
+            if let Some(timeout) = settings.timeout() {
+                self.timeout = Some(timeout);
+            }
+            self
+        }
+
+        /// Applies the settings in `[client]` of the config, and keeps its profiles for `run_profile`.
+        #[cfg(feature = "config")]
+        pub fn with_config(self, config: Config) -> Self {
+            let mut client = self.with_settings(config.client());
+            client.config = Arc::new(config);
+            client
+        }
+
+        /// Sends the query of the profile in the config set by `with_config` in the blocking context,
+        /// applying the client settings of the profile.
+        #[cfg(feature = "config")]
+        pub fn run_profile(&self, name: &str) -> ConnpassResult<ConnpassResponse> {
+            let profile = self
+                .config
+                .profile(name)
+                .ok_or_else(|| unknown_profile(name))?;
+            self.clone()
+                .with_settings(profile.client())
+                .send_request(profile.query().clone())
         }
 
         /// Sends requests and gets response from API in the blocking context.
@@ -151,11 +525,11 @@ pub mod blocking {
         ///
         /// # Example:
         /// ```
-        /// use connpass_rs::{client::blocking::ConnpassClient, query::builder::QueryBuilder};
+        /// use connpass_rs::{client::blocking::ConnpassClient, id::EventId, query::builder::QueryBuilder};
         ///
         /// fn main() {
         ///     // fetch https://rust.connpass.com/event/228732/
-        ///     let query = QueryBuilder::begin().event_id(228732).build();
+        ///     let query = QueryBuilder::begin().event_id(EventId::new(228732)).build();
         ///     if let Ok(query) = query {
         ///         let client = ConnpassClient::new();
         ///         let res = client.send_request(query);
@@ -167,42 +541,198 @@ pub mod blocking {
         /// }
         /// ```
         #[allow(clippy::needless_doctest_main)]
-        pub fn send_request(self, query: Query) -> ConnpassResult<ConnpassResponse> {
-            let response = self
-                .client
-                .get(BASE_URL)
-                .header(USER_AGENT, CRATE_USER_AGENT.as_str())
-                .query(&query.make_reqwest_query())
-                .send()
-                .map_err(|err| {
+        pub fn send_request(&self, mut query: Query) -> ConnpassResult<ConnpassResponse> {
+            self.resolve(&mut query)?;
+            self.send(query)
+        }
+
+        /// Sends the request like `send_request` in the blocking context, but returns the body as it is.
+        /// This is for decoding the body into `response::borrowed::ConnpassResponse`, which borrows strings from it.
+        pub fn send_request_bytes(&self, mut query: Query) -> ConnpassResult<Bytes> {
+            self.resolve(&mut query)?;
+            self.send_raw(query)?
+                .bytes()
+                .map_err(|err| ConnpassCliError::HttpResponse(HttpResponseError::ReqwestError(err)))
+        }
+
+        /// Fetches the event of the id in the blocking context, or returns `None` if it doesn't exist.
+        pub fn get_event(&self, event_id: EventId) -> ConnpassResult<Option<Event>> {
+            let res = self.send(event_query(event_id)?)?;
+            Ok(find_event(res, event_id))
+        }
+
+        /// Fetches the events of the ids in the blocking context. The ids are looked up 100 by 100,
+        /// and the ones not found are reported by `EventLookup::missing`.
+        pub fn get_events(&self, event_ids: &[EventId]) -> ConnpassResult<EventLookup> {
+            let mut found = Vec::new();
+            for query in lookup_queries(event_ids)? {
+                found.extend(self.send(query)?.into_events());
+            }
+            Ok(EventLookup::new(event_ids, found))
+        }
+
+        /// Replaces the group subdomains in the query with their series ids.
+        fn resolve(&self, query: &mut Query) -> ConnpassResult<()> {
+            for subdomain in query.take_group_subdomains() {
+                let series_id = self.resolve_series_id(&subdomain)?;
+                query.push_series_id(series_id);
+            }
+            Ok(())
+        }
+
+        /// Resolves the group subdomain (e.g. `rust` of `rust.connpass.com`) into its series id in the blocking context.
+        /// The result is cached, so this sends requests only for the first time for each subdomain.
+        /// See `resolver` module for how it's resolved.
+        pub fn resolve_series_id(&self, subdomain: &str) -> ConnpassResult<SeriesId> {
+            if let Some(series_id) = self.resolver.cached(subdomain) {
+                return Ok(series_id);
+            }
+            for page in 0..MAX_SAMPLED_PAGES {
+                // The sampling queries are internal, so the policies are not applied to them.
+                let res = self.decode(self.request(sample_query(subdomain, page)?)?)?;
+                if let Some(series_id) = self.resolver.find_in(subdomain, &res) {
+                    return Ok(series_id);
+                }
+                if next_start(&res).is_none() {
+                    break;
+                }
+            }
+            Err(unresolved(subdomain))
+        }
+
+        fn send(&self, query: Query) -> ConnpassResult<ConnpassResponse> {
+            self.decode(self.send_raw(query)?)
+        }
+
+        fn decode(&self, response: Response) -> ConnpassResult<ConnpassResponse> {
+            if self.lenient {
+                let bytes = response.bytes().map_err(|err| {
                     ConnpassCliError::HttpResponse(HttpResponseError::ReqwestError(err))
                 })?;
+                ConnpassResponse::from_slice_lenient(&bytes)
+            } else {
+                response
+                    .json::<ConnpassResponse>()
+                    .map_err(json_decode_error)
+            }
+        }
 
-            self.handler(response)
+        fn send_raw(&self, query: Query) -> ConnpassResult<Response> {
+            self.policies.validate(&query)?;
+            self.request(query)
         }
 
-        fn handler(&self, res: Response) -> ConnpassResult<ConnpassResponse> {
-            dbg!("response = {}", &res);
-            match res.status() {
-                StatusCode::OK => res.json::<ConnpassResponse>().map_err(|err| {
-                    ConnpassCliError::HttpResponse(HttpResponseError::JsonDecode(format!(
-                        "{}",
-                        err
-                    )))
-                }),
-                StatusCode::FORBIDDEN => {
-                    Err(ConnpassCliError::HttpResponse(HttpResponseError::Forbidden))
-                }
-                StatusCode::INTERNAL_SERVER_ERROR => Err(ConnpassCliError::HttpResponse(
-                    HttpResponseError::InternalServerError,
-                )),
-                StatusCode::SERVICE_UNAVAILABLE => Err(ConnpassCliError::HttpResponse(
-                    HttpResponseError::ServiceUnavailable,
-                )),
-                s => Err(ConnpassCliError::HttpResponse(HttpResponseError::Various(
-                    format!("Unexpected response received: {:?} (status code)", s),
-                ))),
+        /// Sends the query without checking the policies, which is only for the queries made for resolving group subdomains.
+        fn request(&self, query: Query) -> ConnpassResult<Response> {
+            let mut request = self
+                .client
+                .get(BASE_URL)
+                .header(USER_AGENT, CRATE_USER_AGENT.as_str())
+                .query(&query.make_reqwest_query());
+            if let Some(timeout) = self.timeout {
+                request = request.timeout(timeout);
             }
+            let response = request.send().map_err(|err| {
+                ConnpassCliError::HttpResponse(HttpResponseError::ReqwestError(err))
+            })?;
+            check_status(response.status())?;
+            Ok(response)
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use crate::{
+        errors::{ConnpassCliError, ValidationError},
+        id::EventId,
+        query::{
+            builder::QueryBuilder,
+            validator::{Policies, RequireCount, RequireDateFilter},
+            Query,
+        },
+        response::ConnpassResponse,
+    };
+
+    use super::{lookup_queries, next_start, ConnpassClient};
+
+    fn response(returned: u32, available: u32, start: u32) -> ConnpassResponse {
+        serde_json::from_value(json!({
+            "results_returned": returned,
+            "results_available": available,
+            "results_start": start,
+            "events": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_next_start() {
+        assert_eq!(next_start(&response(10, 25, 1)), Some(11));
+        assert_eq!(next_start(&response(10, 25, 11)), Some(21));
+        assert_eq!(next_start(&response(5, 25, 21)), None);
+        assert_eq!(next_start(&response(10, 20, 11)), None);
+        assert_eq!(next_start(&response(0, 25, 26)), None);
+    }
+
+    #[test]
+    fn test_lookup_queries() {
+        let ids = |range: std::ops::RangeInclusive<u32>| -> Vec<EventId> {
+            range.map(EventId::new).collect()
+        };
+        let requested: Vec<EventId> = ids(1..=150).into_iter().chain(ids(1..=10)).collect();
+        let queries = lookup_queries(&requested).unwrap();
+        assert_eq!(queries.len(), 2);
+        assert_eq!(queries[0].event_ids(), ids(1..=100));
+        assert_eq!(queries[0].count(), Some(100));
+        assert_eq!(queries[1].event_ids(), ids(101..=150));
+        assert_eq!(queries[1].count(), Some(50));
+        assert!(lookup_queries(&[]).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_policies_check_client_made_queries() {
+        // The query is rejected before being sent, so this doesn't need the network.
+        let client = ConnpassClient::new().with_policies(Policies::new().with(RequireCount(100)));
+        let res = client.get_events(&[EventId::new(1)]).await;
+        assert!(matches!(
+            res,
+            Err(ConnpassCliError::Validation(ValidationError::Fields(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_policies_skip_sampling_queries() {
+        let checked = Arc::new(Mutex::new(Vec::new()));
+        let recorder = checked.clone();
+        let policies = Policies::new()
+            .with(RequireDateFilter)
+            .with(move |query: &Query| {
+                recorder.lock().unwrap().push(query.clone());
+                Vec::new()
+            });
+        let client = ConnpassClient::new()
+            .with_policies(policies)
+            .timeout(Duration::from_secs(5));
+        let query = QueryBuilder::begin()
+            .group_subdomain("rust")
+            .ym(202110)
+            .build()
+            .unwrap();
+        // Whether the network is available or not, the sampling queries must not be rejected by `RequireDateFilter`.
+        let res = client.send_request(query).await;
+        assert!(!matches!(res, Err(ConnpassCliError::Validation(_))));
+        assert!(checked
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|query| query.keywords().is_empty()));
+    }
+}
@@ -0,0 +1,177 @@
+//! Loads named profiles of queries and client settings from a TOML file, so that standard searches can be shared.
+//! This module is available with `config` feature.
+//!
+//! A config looks like:
+//! ```toml
+//! # The settings for every profile, which are applied by `ConnpassClient::with_config`.
+//! [client]
+//! timeout_secs = 30
+//!
+//! [profiles.rust-tokyo]
+//! query = { keyword = ["Rust", "Tokyo"], order = "newer", count = 100 }
+//!
+//! # The settings of a profile override the ones in `[client]`.
+//! [profiles.our-events]
+//! query = { owner_nickname = ["yuk1ty"] }
+//! client = { lenient = true }
+//! ```
+//!
+//! The queries are written in the same way as serializing `Query` with serde, and validated by `QueryBuilder::build`.
+
+use std::{collections::BTreeMap, fs, num::NonZeroU64, path::Path, time::Duration};
+
+use serde::Deserialize;
+
+use crate::{
+    errors::{ConfigError, ConnpassCliError, ConnpassResult},
+    query::Query,
+};
+
+/// Named profiles and the client settings for them.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    client: ClientSettings,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    /// Reads and parses the TOML file.
+    pub fn load(path: impl AsRef<Path>) -> ConnpassResult<Self> {
+        let s = fs::read_to_string(path)
+            .map_err(|err| ConnpassCliError::Config(ConfigError::Io(err)))?;
+        s.parse()
+    }
+
+    pub fn client(&self) -> &ClientSettings {
+        &self.client
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.get(name)
+    }
+
+    /// Returns the names of the profiles in alphabetical order.
+    pub fn profile_names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+}
+
+impl std::str::FromStr for Config {
+    type Err = ConnpassCliError;
+
+    /// Parses the config written in TOML.
+    fn from_str(s: &str) -> ConnpassResult<Self> {
+        toml::from_str(s)
+            .map_err(|err| ConnpassCliError::Config(ConfigError::Parse(err.to_string())))
+    }
+}
+
+/// A named query, and the client settings for sending it.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    query: Query,
+    #[serde(default)]
+    client: ClientSettings,
+}
+
+impl Profile {
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// Returns the client settings of the profile, which don't include the ones in `[client]`.
+    pub fn client(&self) -> &ClientSettings {
+        &self.client
+    }
+}
+
+/// Settings of `ConnpassClient`. The unset ones keep the settings of the client as they are.
+#[derive(Deserialize, PartialEq, Clone, Copy, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ClientSettings {
+    lenient: Option<bool>,
+    /// A zero timeout is rejected in parsing, since every request would time out.
+    timeout_secs: Option<NonZeroU64>,
+}
+
+impl ClientSettings {
+    /// Whether to decode responses in the lenient mode. See `ConnpassClient::lenient`.
+    pub fn lenient(&self) -> Option<bool> {
+        self.lenient
+    }
+
+    /// The timeout of each request. See `ConnpassClient::timeout`.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_secs
+            .map(|secs| Duration::from_secs(secs.get()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        errors::{ConfigError, ConnpassCliError},
+        query::types::OrderOption,
+    };
+
+    use super::Config;
+
+    const CONFIG: &str = r#"
+[client]
+timeout_secs = 30
+
+[profiles.rust-tokyo]
+query = { keyword = ["Rust", "Tokyo"], order = "newer", count = 100 }
+
+[profiles.our-events]
+query = { owner_nickname = ["yuk1ty"] }
+client = { lenient = true }
+"#;
+
+    #[test]
+    fn test_parse_config() {
+        let config: Config = CONFIG.parse().unwrap();
+        assert_eq!(config.client().timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(
+            config.profile_names().collect::<Vec<_>>(),
+            vec!["our-events", "rust-tokyo"]
+        );
+
+        let profile = config.profile("rust-tokyo").unwrap();
+        assert_eq!(profile.query().keywords(), ["Rust", "Tokyo"]);
+        assert_eq!(profile.query().order(), Some(OrderOption::Newer));
+        assert_eq!(profile.query().count(), Some(100));
+        assert_eq!(profile.client().lenient(), None);
+
+        let profile = config.profile("our-events").unwrap();
+        assert_eq!(profile.client().lenient(), Some(true));
+        assert!(config.profile("unknown").is_none());
+    }
+
+    #[test]
+    fn test_parse_invalid_config() {
+        for s in [
+            "[profiles.a]\nquery = { count = 0 }",
+            "[profiles.a]\nquery = { unknown = 1 }",
+            "[profiles.a]\nclient = { lenient = true }",
+            "[client]\ntimeout_secs = 0",
+            "[profiles.a]\nquery = {}\nclient = { timeout_secs = 0 }",
+            "[unknown]",
+        ] {
+            assert!(
+                matches!(
+                    s.parse::<Config>(),
+                    Err(ConnpassCliError::Config(ConfigError::Parse(_)))
+                ),
+                "{}",
+                s
+            );
+        }
+    }
+}
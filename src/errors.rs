@@ -2,6 +2,8 @@
 
 use thiserror::Error;
 
+use crate::id::EventId;
+
 /// General errors for this crate.
 #[derive(Debug, Error)]
 #[error(transparent)]
@@ -10,6 +12,14 @@ pub enum ConnpassCliError {
     Validation(ValidationError),
     /// Errors around HTTP connection including JSON decoding, status code, etc.
     HttpResponse(HttpResponseError),
+    /// Errors around exporting events into files.
+    Export(ExportError),
+    /// Errors around loading config files.
+    Config(ConfigError),
+    /// Errors around loading and saving the state of seen events.
+    Store(StoreError),
+    /// Errors around resolving group subdomains into series ids.
+    Resolve(ResolveError),
 }
 
 /// Represents errors around validation.
@@ -21,8 +31,71 @@ pub enum ValidationError {
     /// Uses when unexpected token is passed to a value.
     #[error("{msg}")]
     InvalidToken { msg: String },
+    /// Uses when building a query failed. Every problem found in the query is listed.
+    #[error(transparent)]
+    Fields(ValidationErrors),
+    /// Uses when an event is compared with or patched by the snapshots of another event.
+    #[error("Expected the event {expected}, but found the event {found}")]
+    EventIdMismatch { expected: EventId, found: EventId },
+}
+
+/// A problem of a field of a query.
+#[derive(Debug, Error)]
+#[error("`{field}`: {error}")]
+pub struct FieldError {
+    field: &'static str,
+    error: ValidationError,
 }
 
+impl FieldError {
+    pub fn new(field: &'static str, error: ValidationError) -> Self {
+        FieldError { field, error }
+    }
+
+    /// Returns the name of the field, which is the same as the query parameter of connpass API, e.g. `count`.
+    pub fn field(&self) -> &'static str {
+        self.field
+    }
+
+    pub fn error(&self) -> &ValidationError {
+        &self.error
+    }
+}
+
+/// Every problem found in validating a query, in the order of the fields.
+#[derive(Debug)]
+pub struct ValidationErrors {
+    errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    pub(crate) fn new(errors: Vec<FieldError>) -> Self {
+        ValidationErrors { errors }
+    }
+
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> Vec<FieldError> {
+        self.errors
+    }
+}
+
+impl std::fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
 /// Represents errors around HTTP connection.
 #[derive(Debug, Error)]
 pub enum HttpResponseError {
@@ -46,4 +119,51 @@ pub enum HttpResponseError {
     ReqwestError(#[from] reqwest::Error),
 }
 
+/// Represents errors around exporting events.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    /// Uses when writing to the destination failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Uses when encoding into CSV failed.
+    #[error("{0}")]
+    Csv(String),
+    /// Uses when encoding into JSON failed.
+    #[error("{0}")]
+    Json(String),
+}
+
+/// Represents errors around loading config files.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// Uses when reading the file failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Uses when parsing the config failed.
+    #[error("{0}")]
+    Parse(String),
+    /// Uses when the profile is not defined in the config.
+    #[error("Unknown profile: {0}")]
+    UnknownProfile(String),
+}
+
+/// Represents errors around the state of seen events.
+#[derive(Debug, Error)]
+pub enum StoreError {
+    /// Uses when reading or writing the state failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Uses when encoding or decoding the state failed.
+    #[error("{0}")]
+    Json(String),
+}
+
+/// Represents errors around resolving group subdomains.
+#[derive(Debug, Error)]
+pub enum ResolveError {
+    /// Uses when no event held by the group was found in the sampled pages.
+    #[error("Could not resolve the group subdomain `{0}` into a series id")]
+    SubdomainNotFound(String),
+}
+
 pub type ConnpassResult<T> = core::result::Result<T, ConnpassCliError>;
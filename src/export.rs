@@ -0,0 +1,414 @@
+//! Writes events into CSV or JSON Lines, e.g. for handing event lists over to spreadsheets.
+//! This module is available when `export` feature is enabled.
+//!
+//! `series` is flattened into `series_id`, `series_title` and `series_url` columns.
+//! The writers write each event as soon as it's passed, so they can be used with `ConnpassClient::pages`
+//! without loading all the pages into memory.
+
+use std::{io::Write, str::FromStr};
+
+use futures::{Stream, StreamExt};
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde_json::{json, Value};
+
+use crate::{
+    errors::{ConnpassCliError, ConnpassResult, ExportError, ValidationError},
+    response::{ConnpassResponse, Event},
+};
+
+/// A column of the exported data.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Column {
+    EventId,
+    Title,
+    Catch,
+    Description,
+    EventUrl,
+    HashTag,
+    StartedAt,
+    EndedAt,
+    Limit,
+    EventType,
+    SeriesId,
+    SeriesTitle,
+    SeriesUrl,
+    Address,
+    Place,
+    Lat,
+    Lon,
+    OwnerId,
+    OwnerNickname,
+    OwnerDisplayName,
+    Accepted,
+    Waiting,
+    UpdatedAt,
+}
+
+impl Column {
+    /// All the columns in the order of the fields declared in `Event`.
+    pub const ALL: [Column; 23] = [
+        Column::EventId,
+        Column::Title,
+        Column::Catch,
+        Column::Description,
+        Column::EventUrl,
+        Column::HashTag,
+        Column::StartedAt,
+        Column::EndedAt,
+        Column::Limit,
+        Column::EventType,
+        Column::SeriesId,
+        Column::SeriesTitle,
+        Column::SeriesUrl,
+        Column::Address,
+        Column::Place,
+        Column::Lat,
+        Column::Lon,
+        Column::OwnerId,
+        Column::OwnerNickname,
+        Column::OwnerDisplayName,
+        Column::Accepted,
+        Column::Waiting,
+        Column::UpdatedAt,
+    ];
+
+    /// Returns the name used for the CSV header and the JSON key.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::EventId => "event_id",
+            Column::Title => "title",
+            Column::Catch => "catch",
+            Column::Description => "description",
+            Column::EventUrl => "event_url",
+            Column::HashTag => "hash_tag",
+            Column::StartedAt => "started_at",
+            Column::EndedAt => "ended_at",
+            Column::Limit => "limit",
+            Column::EventType => "event_type",
+            Column::SeriesId => "series_id",
+            Column::SeriesTitle => "series_title",
+            Column::SeriesUrl => "series_url",
+            Column::Address => "address",
+            Column::Place => "place",
+            Column::Lat => "lat",
+            Column::Lon => "lon",
+            Column::OwnerId => "owner_id",
+            Column::OwnerNickname => "owner_nickname",
+            Column::OwnerDisplayName => "owner_display_name",
+            Column::Accepted => "accepted",
+            Column::Waiting => "waiting",
+            Column::UpdatedAt => "updated_at",
+        }
+    }
+
+    fn value(&self, event: &Event) -> Value {
+        let series = event.series();
+        match self {
+            Column::EventId => json!(event.id()),
+            Column::Title => json!(event.title()),
+            Column::Catch => json!(event.catch()),
+            Column::Description => json!(event.description()),
+            Column::EventUrl => json!(event.event_url()),
+            Column::HashTag => json!(event.hash_tag()),
+            Column::StartedAt => json!(event.started_at()),
+            Column::EndedAt => json!(event.ended_at()),
+            Column::Limit => json!(event.limit()),
+            Column::EventType => json!(event.event_type()),
+            Column::SeriesId => json!(series.map(|s| s.id())),
+            Column::SeriesTitle => json!(series.and_then(|s| s.title())),
+            Column::SeriesUrl => json!(series.and_then(|s| s.url())),
+            Column::Address => json!(event.address()),
+            Column::Place => json!(event.place()),
+            Column::Lat => json!(event.lat()),
+            Column::Lon => json!(event.lon()),
+            Column::OwnerId => json!(event.owner_id()),
+            Column::OwnerNickname => json!(event.owner_nickname()),
+            Column::OwnerDisplayName => json!(event.owner_display_name()),
+            Column::Accepted => json!(event.accepted()),
+            Column::Waiting => json!(event.waiting()),
+            Column::UpdatedAt => json!(event.updated_at()),
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = ConnpassCliError;
+
+    fn from_str(s: &str) -> ConnpassResult<Self> {
+        Column::ALL
+            .iter()
+            .find(|c| c.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                ConnpassCliError::Validation(ValidationError::InvalidToken {
+                    msg: format!("Unknown column: {}", s),
+                })
+            })
+    }
+}
+
+/// Common interface of the writers in this module.
+pub trait EventWriter {
+    /// Writes a single event.
+    fn write_event(&mut self, event: &Event) -> ConnpassResult<()>;
+
+    /// Flushes the underlying writer.
+    fn flush(&mut self) -> ConnpassResult<()>;
+
+    /// Writes all the events in the response.
+    fn write_response(&mut self, res: &ConnpassResponse) -> ConnpassResult<()> {
+        for event in res.events() {
+            self.write_event(event)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes all the events in the pages, one page at a time, then flushes the writer.
+/// Stops at the first error either from the stream or from the writer.
+///
+/// # Example:
+/// ```no_run
+/// use std::fs::File;
+///
+/// use connpass_rs::{
+///     client::ConnpassClient,
+///     export::{write_pages, Column, CsvWriter},
+///     query::builder::QueryBuilder,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let query = QueryBuilder::begin().keyword("Rust").count(100).build().unwrap();
+///     let client = ConnpassClient::new();
+///     let file = File::create("events.csv").unwrap();
+///     let mut writer = CsvWriter::new(file, Column::ALL.to_vec()).unwrap();
+///     write_pages(&mut writer, client.pages(query)).await.unwrap();
+/// }
+/// ```
+pub async fn write_pages<W, S>(writer: &mut W, pages: S) -> ConnpassResult<()>
+where
+    W: EventWriter,
+    S: Stream<Item = ConnpassResult<ConnpassResponse>>,
+{
+    futures::pin_mut!(pages);
+    while let Some(page) = pages.next().await {
+        writer.write_response(&page?)?;
+    }
+    writer.flush()
+}
+
+/// Writes events into CSV with a header row.
+pub struct CsvWriter<W: Write> {
+    writer: csv::Writer<W>,
+    columns: Vec<Column>,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Initializes the writer and writes the header row immediately.
+    pub fn new(writer: W, columns: Vec<Column>) -> ConnpassResult<Self> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer
+            .write_record(columns.iter().map(Column::name))
+            .map_err(csv_error)?;
+        Ok(CsvWriter { writer, columns })
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn into_inner(self) -> ConnpassResult<W> {
+        self.writer.into_inner().map_err(|err| {
+            ConnpassCliError::Export(ExportError::Io(std::io::Error::new(
+                err.error().kind(),
+                err.error().to_string(),
+            )))
+        })
+    }
+}
+
+impl<W: Write> EventWriter for CsvWriter<W> {
+    fn write_event(&mut self, event: &Event) -> ConnpassResult<()> {
+        let record = self.columns.iter().map(|c| match c.value(event) {
+            Value::Null => String::new(),
+            Value::String(s) => s,
+            v => v.to_string(),
+        });
+        self.writer.write_record(record).map_err(csv_error)
+    }
+
+    fn flush(&mut self) -> ConnpassResult<()> {
+        self.writer
+            .flush()
+            .map_err(|err| ConnpassCliError::Export(ExportError::Io(err)))
+    }
+}
+
+/// Writes events into JSON Lines, i.e. a JSON object per line.
+/// Each object has the selected columns as its keys, and a missing value is written as `null`.
+pub struct JsonLinesWriter<W: Write> {
+    writer: W,
+    columns: Vec<Column>,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    pub fn new(writer: W, columns: Vec<Column>) -> Self {
+        JsonLinesWriter { writer, columns }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> EventWriter for JsonLinesWriter<W> {
+    fn write_event(&mut self, event: &Event) -> ConnpassResult<()> {
+        let row = Row {
+            columns: &self.columns,
+            event,
+        };
+        serde_json::to_writer(&mut self.writer, &row)
+            .map_err(|err| ConnpassCliError::Export(ExportError::Json(format!("{}", err))))?;
+        self.writer
+            .write_all(b"\n")
+            .map_err(|err| ConnpassCliError::Export(ExportError::Io(err)))
+    }
+
+    fn flush(&mut self) -> ConnpassResult<()> {
+        self.writer
+            .flush()
+            .map_err(|err| ConnpassCliError::Export(ExportError::Io(err)))
+    }
+}
+
+/// A row of JSON Lines, whose keys are written in the order of the columns.
+struct Row<'a> {
+    columns: &'a [Column],
+    event: &'a Event,
+}
+
+impl Serialize for Row<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for column in self.columns {
+            map.serialize_entry(column.name(), &column.value(self.event))?;
+        }
+        map.end()
+    }
+}
+
+fn csv_error(err: csv::Error) -> ConnpassCliError {
+    ConnpassCliError::Export(ExportError::Csv(format!("{}", err)))
+}
+
+#[cfg(test)]
+mod test {
+    use futures::{executor::block_on, stream};
+    use serde_json::json;
+
+    use crate::{
+        errors::{ConnpassCliError, ValidationError},
+        response::ConnpassResponse,
+    };
+
+    use super::{write_pages, Column, CsvWriter, EventWriter, JsonLinesWriter};
+
+    fn response(start: u32, ids: &[u32]) -> ConnpassResponse {
+        let events: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                json!({
+                    "event_id": id,
+                    "title": "Rust, \"Tokyo\"",
+                    "limit": 30,
+                    "event_type": "participation",
+                    "series": { "id": 4424, "title": "Rust.Tokyo", "url": "https://rust.connpass.com/" },
+                })
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "results_returned": ids.len(),
+            "results_available": 3,
+            "results_start": start,
+            "events": events,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_column_from_str() {
+        assert_eq!("series_url".parse::<Column>().unwrap(), Column::SeriesUrl);
+        assert!(matches!(
+            "series".parse::<Column>(),
+            Err(ConnpassCliError::Validation(
+                ValidationError::InvalidToken { msg: _ }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_csv_writer() {
+        let columns = vec![
+            Column::EventId,
+            Column::Title,
+            Column::Place,
+            Column::SeriesId,
+            Column::EventType,
+        ];
+        let mut writer = CsvWriter::new(Vec::new(), columns).unwrap();
+        writer.write_response(&response(1, &[1, 2])).unwrap();
+        let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "event_id,title,place,series_id,event_type\n\
+             1,\"Rust, \"\"Tokyo\"\"\",,4424,participation\n\
+             2,\"Rust, \"\"Tokyo\"\"\",,4424,participation\n"
+        );
+    }
+
+    #[test]
+    fn test_json_lines_writer() {
+        let columns = vec![
+            Column::EventId,
+            Column::Limit,
+            Column::Place,
+            Column::SeriesTitle,
+        ];
+        let mut writer = JsonLinesWriter::new(Vec::new(), columns);
+        writer.write_response(&response(1, &[1, 2])).unwrap();
+        let jsonl = String::from_utf8(writer.into_inner()).unwrap();
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                json!({ "event_id": 1, "limit": 30, "place": null, "series_title": "Rust.Tokyo" }),
+                json!({ "event_id": 2, "limit": 30, "place": null, "series_title": "Rust.Tokyo" }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_lines_writer_keeps_column_order() {
+        let columns = vec![Column::Title, Column::EventId, Column::Limit];
+        let mut writer = JsonLinesWriter::new(Vec::new(), columns);
+        writer.write_response(&response(1, &[1])).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "{\"title\":\"Rust, \\\"Tokyo\\\"\",\"event_id\":1,\"limit\":30}\n"
+        );
+    }
+
+    #[test]
+    fn test_write_pages() {
+        let pages = stream::iter(vec![Ok(response(1, &[1, 2])), Ok(response(3, &[3]))]);
+        let mut writer = JsonLinesWriter::new(Vec::new(), vec![Column::EventId]);
+        block_on(write_pages(&mut writer, pages)).unwrap();
+        let jsonl = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(
+            jsonl,
+            "{\"event_id\":1}\n{\"event_id\":2}\n{\"event_id\":3}\n"
+        );
+    }
+}
@@ -0,0 +1,342 @@
+//! Renders events as an Atom or RSS 2.0 feed.
+//! This module is available when `feed` feature is enabled.
+//!
+//! Each entry is keyed by `event_id`, uses `updated_at` as its update time, and carries the event description as its content.
+
+use std::{collections::HashMap, time::SystemTime};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use futures::{Stream, StreamExt};
+
+use crate::{
+    errors::ConnpassResult,
+    id::EventId,
+    response::{ConnpassResponse, Event},
+};
+
+const DEFAULT_AUTHOR: &str = "connpass";
+
+/// Builder for a feed.
+///
+/// # Example:
+/// ```no_run
+/// use connpass_rs::{client::ConnpassClient, feed::Feed, query::builder::QueryBuilder};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let query = QueryBuilder::begin().keyword("Rust").count(100).build().unwrap();
+///     let client = ConnpassClient::new();
+///     let mut feed = Feed::new("Rust events in Japan", "https://example.com/rust-events.atom");
+///     feed.extend_pages(client.pages(query)).await.unwrap();
+///     println!("{}", feed.to_atom());
+/// }
+/// ```
+pub struct Feed {
+    title: String,
+    link: String,
+    subtitle: Option<String>,
+    author: String,
+    entries: Vec<Event>,
+    positions: HashMap<EventId, usize>,
+}
+
+impl Feed {
+    /// Initializes `Feed`. `link` is the URL of the feed itself, which is also used as the feed id.
+    pub fn new(title: impl Into<String>, link: impl Into<String>) -> Self {
+        Feed {
+            title: title.into(),
+            link: link.into(),
+            subtitle: None,
+            author: DEFAULT_AUTHOR.to_string(),
+            entries: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Sets the subtitle of Atom, which is also used as the description of RSS.
+    pub fn subtitle(mut self, subtitle: impl Into<String>) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// Sets the author of the feed. Defaults to "connpass".
+    /// The owner of each event is used as the author of its entry if available.
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = author.into();
+        self
+    }
+
+    /// Adds the event as an entry.
+    /// If an entry with the same `event_id` already exists, it's replaced.
+    pub fn push(&mut self, event: Event) {
+        match self.positions.get(&event.id()) {
+            Some(&i) => self.entries[i] = event,
+            None => {
+                self.positions.insert(event.id(), self.entries.len());
+                self.entries.push(event);
+            }
+        }
+    }
+
+    /// Adds all the events in the response.
+    pub fn push_response(&mut self, res: ConnpassResponse) {
+        for event in res.into_events() {
+            self.push(event);
+        }
+    }
+
+    /// Adds all the events in the pages, e.g. from `ConnpassClient::pages`.
+    /// Stops at the first error.
+    pub async fn extend_pages<S>(&mut self, pages: S) -> ConnpassResult<()>
+    where
+        S: Stream<Item = ConnpassResult<ConnpassResponse>>,
+    {
+        futures::pin_mut!(pages);
+        while let Some(page) = pages.next().await {
+            self.push_response(page?);
+        }
+        Ok(())
+    }
+
+    /// Renders the feed as Atom (RFC 4287).
+    pub fn to_atom(&self) -> String {
+        let mut xml = String::new();
+        xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+        xml.push('\n');
+        xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+        push_element(&mut xml, "title", &self.title);
+        if let Some(subtitle) = &self.subtitle {
+            push_element(&mut xml, "subtitle", subtitle);
+        }
+        push_element(&mut xml, "id", &self.link);
+        xml.push_str(&format!(
+            r#"<link rel="self" href="{}"/>"#,
+            escape(&self.link)
+        ));
+        let updated = self.updated();
+        push_element(&mut xml, "updated", &updated.to_rfc3339());
+        xml.push_str("<author>");
+        push_element(&mut xml, "name", &self.author);
+        xml.push_str("</author>");
+
+        for event in &self.entries {
+            xml.push_str("<entry>");
+            push_element(&mut xml, "title", event.title().unwrap_or_default());
+            push_element(&mut xml, "id", &entry_id(event));
+            if let Some(url) = event.event_url() {
+                xml.push_str(&format!(
+                    r#"<link rel="alternate" href="{}"/>"#,
+                    escape(url)
+                ));
+            }
+            // `updated` is required for each entry, so the one of the feed is used if the event lacks it.
+            let updated_at = updated_at(event).unwrap_or(updated);
+            push_element(&mut xml, "updated", &updated_at.to_rfc3339());
+            if let Some(owner) = owner(event) {
+                xml.push_str("<author>");
+                push_element(&mut xml, "name", owner);
+                xml.push_str("</author>");
+            }
+            if let Some(catch) = event.catch().filter(|c| !c.is_empty()) {
+                push_element(&mut xml, "summary", catch);
+            }
+            if let Some(description) = event.description() {
+                xml.push_str(&format!(
+                    r#"<content type="html">{}</content>"#,
+                    escape(description)
+                ));
+            }
+            xml.push_str("</entry>");
+        }
+
+        xml.push_str("</feed>\n");
+        xml
+    }
+
+    /// Renders the feed as RSS 2.0.
+    pub fn to_rss(&self) -> String {
+        let mut xml = String::new();
+        xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+        xml.push('\n');
+        xml.push_str(r#"<rss version="2.0"><channel>"#);
+        push_element(&mut xml, "title", &self.title);
+        push_element(&mut xml, "link", &self.link);
+        push_element(
+            &mut xml,
+            "description",
+            self.subtitle.as_deref().unwrap_or(&self.title),
+        );
+        push_element(&mut xml, "lastBuildDate", &self.updated().to_rfc2822());
+
+        for event in &self.entries {
+            xml.push_str("<item>");
+            push_element(&mut xml, "title", event.title().unwrap_or_default());
+            if let Some(url) = event.event_url() {
+                push_element(&mut xml, "link", url);
+            }
+            xml.push_str(&format!(
+                r#"<guid isPermaLink="false">{}</guid>"#,
+                escape(&entry_id(event))
+            ));
+            if let Some(updated_at) = updated_at(event) {
+                push_element(&mut xml, "pubDate", &updated_at.to_rfc2822());
+            }
+            if let Some(description) = event.description() {
+                push_element(&mut xml, "description", description);
+            }
+            xml.push_str("</item>");
+        }
+
+        xml.push_str("</channel></rss>\n");
+        xml
+    }
+
+    /// The latest `updated_at` of the entries, or the current time if no entry has it.
+    fn updated(&self) -> DateTime<FixedOffset> {
+        self.entries
+            .iter()
+            .filter_map(updated_at)
+            .max()
+            .unwrap_or_else(|| DateTime::<Utc>::from(SystemTime::now()).into())
+    }
+}
+
+fn entry_id(event: &Event) -> String {
+    format!("https://connpass.com/event/{}/", event.id())
+}
+
+fn updated_at(event: &Event) -> Option<DateTime<FixedOffset>> {
+    event
+        .updated_at()
+        .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+}
+
+fn owner(event: &Event) -> Option<&str> {
+    event
+        .owner_display_name()
+        .or_else(|| event.owner_nickname())
+        .filter(|o| !o.is_empty())
+}
+
+fn push_element(xml: &mut String, name: &str, text: &str) {
+    xml.push_str(&format!("<{}>{}</{}>", name, escape(text), name));
+}
+
+/// Escapes the text for XML, dropping the control characters XML 1.0 doesn't allow.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::response::{ConnpassResponse, Event};
+
+    use super::{escape, Feed};
+
+    fn event(id: u32, title: &str, updated_at: &str) -> Event {
+        serde_json::from_value(json!({
+            "event_id": id,
+            "title": title,
+            "catch": "Rust & Tokyo",
+            "description": "<p>Rust.Tokyo</p>",
+            "event_url": format!("https://rust.connpass.com/event/{}/", id),
+            "owner_display_name": "yuk1ty",
+            "updated_at": updated_at,
+        }))
+        .unwrap()
+    }
+
+    fn feed() -> Feed {
+        let res: ConnpassResponse = serde_json::from_value(json!({
+            "results_returned": 2,
+            "results_available": 2,
+            "results_start": 1,
+            "events": [
+                event(1, "Rust.Tokyo", "2021-10-01T10:00:00+09:00"),
+                event(2, "Rust.Osaka", "2021-10-03T10:00:00+09:00"),
+            ],
+        }))
+        .unwrap();
+        let mut feed = Feed::new("Rust events", "https://example.com/feed").subtitle("in Japan");
+        feed.push_response(res);
+        feed.push(event(1, "Rust.Tokyo 2021", "2021-10-02T10:00:00+09:00"));
+        feed
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("<a href=\"x\">R&D's</a>\u{8}"),
+            "&lt;a href=&quot;x&quot;&gt;R&amp;D&apos;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_push_replaces_same_event_id() {
+        let feed = feed();
+        assert_eq!(feed.entries.len(), 2);
+        assert_eq!(feed.entries[0].title(), Some("Rust.Tokyo 2021"));
+    }
+
+    #[test]
+    fn test_to_atom() {
+        let atom = feed().to_atom();
+        assert!(atom.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Rust events</title><subtitle>in Japan</subtitle>"));
+        assert!(atom.contains(
+            "<updated>2021-10-03T10:00:00+09:00</updated><author><name>connpass</name></author>"
+        ));
+        assert!(atom.contains(
+            "<entry><title>Rust.Tokyo 2021</title>\
+             <id>https://connpass.com/event/1/</id>\
+             <link rel=\"alternate\" href=\"https://rust.connpass.com/event/1/\"/>\
+             <updated>2021-10-02T10:00:00+09:00</updated>\
+             <author><name>yuk1ty</name></author>\
+             <summary>Rust &amp; Tokyo</summary>\
+             <content type=\"html\">&lt;p&gt;Rust.Tokyo&lt;/p&gt;</content></entry>"
+        ));
+        assert!(atom.ends_with("</feed>\n"));
+    }
+
+    #[test]
+    fn test_to_atom_without_updated_at() {
+        let mut feed = feed();
+        feed.push(
+            serde_json::from_value(json!({ "event_id": 3, "title": "Rust.Fukuoka" })).unwrap(),
+        );
+        let atom = feed.to_atom();
+        assert!(atom.contains(
+            "<entry><title>Rust.Fukuoka</title>\
+             <id>https://connpass.com/event/3/</id>\
+             <updated>2021-10-03T10:00:00+09:00</updated></entry>"
+        ));
+    }
+
+    #[test]
+    fn test_to_rss() {
+        let rss = feed().to_rss();
+        assert!(rss.contains("<channel><title>Rust events</title><link>https://example.com/feed</link><description>in Japan</description><lastBuildDate>Sun, 3 Oct 2021 10:00:00 +0900</lastBuildDate>"));
+        assert!(rss.contains(
+            "<item><title>Rust.Osaka</title>\
+             <link>https://rust.connpass.com/event/2/</link>\
+             <guid isPermaLink=\"false\">https://connpass.com/event/2/</guid>\
+             <pubDate>Sun, 3 Oct 2021 10:00:00 +0900</pubDate>\
+             <description>&lt;p&gt;Rust.Tokyo&lt;/p&gt;</description></item>"
+        ));
+        assert!(rss.ends_with("</channel></rss>\n"));
+    }
+}
@@ -0,0 +1,888 @@
+//! Converts HTML in `Event::description` into plain text or Markdown, e.g. for posting to Slack or printing in terminals.
+//! This module is available when `html` feature is enabled.
+//!
+//! Elements that are unsafe or meaningless outside a browser (`<script>`, `<style>`, `<iframe>`, `<svg>`, etc.) are dropped
+//! together with their contents. `sanitize` applies the same rule and returns HTML.
+
+use crate::response::Event;
+
+/// Elements dropped together with their contents.
+const DROPPED_WITH_CONTENTS: [&str; 11] = [
+    "script", "style", "iframe", "object", "embed", "noscript", "template", "frame", "frameset",
+    "svg", "math",
+];
+/// Elements kept by `sanitize`. The tags of the others are dropped, but their contents are kept.
+const ALLOWED_ELEMENTS: [&str; 49] = [
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "dd",
+    "del",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "u",
+    "ul",
+];
+/// Elements that never have contents nor end tags.
+const VOID_ELEMENTS: [&str; 9] = [
+    "br", "hr", "img", "input", "link", "meta", "base", "embed", "wbr",
+];
+/// Attributes kept by `sanitize` in addition to `href` and `src`, which are checked by their URLs.
+const ALLOWED_ATTRS: [&str; 18] = [
+    "alt", "title", "width", "height", "class", "id", "name", "target", "rel", "colspan",
+    "rowspan", "align", "valign", "lang", "dir", "cite", "datetime", "start",
+];
+/// URL schemes allowed in `href` and `src`. Relative URLs are allowed as well.
+const ALLOWED_SCHEMES: [&str; 3] = ["http:", "https:", "mailto:"];
+
+impl Event {
+    /// Returns the description converted into plain text.
+    pub fn description_text(&self) -> Option<String> {
+        self.description().map(to_text)
+    }
+
+    /// Returns the description converted into Markdown.
+    pub fn description_markdown(&self) -> Option<String> {
+        self.description().map(to_markdown)
+    }
+}
+
+/// Converts the HTML into plain text.
+/// Blocks are separated by blank lines, list items are prefixed by "- " or "1. ",
+/// and links are followed by their URLs in parentheses unless the text is the URL itself.
+pub fn to_text(html: &str) -> String {
+    Renderer::new(Mode::Text).render(html)
+}
+
+/// Converts the HTML into Markdown, preserving headings, lists, links, emphasis, code, images and tables.
+pub fn to_markdown(html: &str) -> String {
+    Renderer::new(Mode::Markdown).render(html)
+}
+
+/// Drops the unsafe elements, comments, event handler attributes (`on*`) and URLs with disallowed schemes
+/// (e.g. `javascript:`), then returns the rest as HTML.
+/// Only common elements for documents and their common attributes are kept, and the contents of the other elements are kept as text.
+pub fn sanitize(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut dropping: Option<String> = None;
+
+    for token in Tokenizer::new(html) {
+        if let Some(dropped) = &dropping {
+            if matches!(&token, Token::End { name } if name == dropped) {
+                dropping = None;
+            }
+            continue;
+        }
+        match token {
+            Token::Start {
+                name,
+                attrs,
+                self_closing,
+            } => {
+                if DROPPED_WITH_CONTENTS.contains(&name.as_str()) {
+                    if !self_closing && !VOID_ELEMENTS.contains(&name.as_str()) {
+                        dropping = Some(name);
+                    }
+                    continue;
+                }
+                if !ALLOWED_ELEMENTS.contains(&name.as_str()) {
+                    continue;
+                }
+                out.push('<');
+                out.push_str(&name);
+                for (key, value) in attrs {
+                    if !is_allowed_attr(&key, &value) {
+                        continue;
+                    }
+                    out.push_str(&format!(" {}=\"{}\"", key, escape_attr(&value)));
+                }
+                if self_closing {
+                    out.push_str(" /");
+                }
+                out.push('>');
+            }
+            Token::End { name } => {
+                if !ALLOWED_ELEMENTS.contains(&name.as_str()) {
+                    continue;
+                }
+                out.push_str(&format!("</{}>", name));
+            }
+            Token::Text(text) => out.push_str(text),
+        }
+    }
+    out
+}
+
+fn is_allowed_attr(key: &str, value: &str) -> bool {
+    if key == "href" || key == "src" {
+        return is_allowed_url(value);
+    }
+    ALLOWED_ATTRS.contains(&key)
+}
+
+fn is_allowed_url(url: &str) -> bool {
+    let url = url.trim().to_ascii_lowercase();
+    match url.find(':') {
+        // A colon after a slash, a question mark or a hash is not a scheme separator.
+        Some(colon) if !url[..colon].contains(['/', '?', '#']) => {
+            ALLOWED_SCHEMES.iter().any(|s| url.starts_with(s))
+        }
+        _ => true,
+    }
+}
+
+fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[derive(PartialEq, Debug)]
+pub(crate) enum Token<'a> {
+    Text(&'a str),
+    Start {
+        name: String,
+        attrs: Vec<(String, String)>,
+        self_closing: bool,
+    },
+    End {
+        name: String,
+    },
+}
+
+/// A lenient HTML tokenizer. Comments and doctypes are skipped,
+/// and the contents of `<script>` and `<style>` are returned as a single text token.
+pub(crate) struct Tokenizer<'a> {
+    rest: &'a str,
+    raw_text_end: Option<String>,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub(crate) fn new(html: &'a str) -> Self {
+        Tokenizer {
+            rest: html,
+            raw_text_end: None,
+        }
+    }
+
+    fn parse_tag(tag: &str) -> Option<Token<'a>> {
+        if let Some(end) = tag.strip_prefix('/') {
+            let name = tag_name(end);
+            return if name.is_empty() {
+                None
+            } else {
+                Some(Token::End { name })
+            };
+        }
+        let name = tag_name(tag);
+        if name.is_empty() {
+            return None;
+        }
+        let self_closing = tag.ends_with('/');
+        let attrs = parse_attrs(tag[name.len()..].trim_end_matches('/'));
+        Some(Token::Start {
+            name,
+            attrs,
+            self_closing,
+        })
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+
+            if let Some(end) = self.raw_text_end.take() {
+                let pos = find_ignore_ascii_case(self.rest, &end).unwrap_or(self.rest.len());
+                let (text, rest) = self.rest.split_at(pos);
+                self.rest = rest;
+                if !text.is_empty() {
+                    return Some(Token::Text(text));
+                }
+                continue;
+            }
+
+            if !self.rest.starts_with('<') {
+                let pos = self.rest.find('<').unwrap_or(self.rest.len());
+                let (text, rest) = self.rest.split_at(pos);
+                self.rest = rest;
+                return Some(Token::Text(text));
+            }
+
+            if let Some(comment) = self.rest.strip_prefix("<!--") {
+                self.rest = comment
+                    .find("-->")
+                    .map(|end| &comment[end + 3..])
+                    .unwrap_or("");
+                continue;
+            }
+
+            let after = &self.rest[1..];
+            let starts_tag = after
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!');
+            if !starts_tag {
+                // A bare "<" is just a text.
+                let pos = after.find('<').map(|p| p + 1).unwrap_or(self.rest.len());
+                let (text, rest) = self.rest.split_at(pos);
+                self.rest = rest;
+                return Some(Token::Text(text));
+            }
+
+            let end = find_tag_end(after);
+            let tag = &after[..end];
+            self.rest = after.get(end + 1..).unwrap_or("");
+            if tag.starts_with('!') {
+                continue;
+            }
+            if let Some(token) = Self::parse_tag(tag.trim()) {
+                if let Token::Start {
+                    name,
+                    self_closing: false,
+                    ..
+                } = &token
+                {
+                    if name == "script" || name == "style" {
+                        self.raw_text_end = Some(format!("</{}", name));
+                    }
+                }
+                return Some(token);
+            }
+        }
+    }
+}
+
+/// Finds the position of '>' closing the tag, ignoring the ones in quoted attribute values.
+fn find_tag_end(tag: &str) -> usize {
+    let mut quote: Option<char> = None;
+    for (i, c) in tag.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '>') => return i,
+            _ => {}
+        }
+    }
+    tag.len()
+}
+
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+fn tag_name(tag: &str) -> String {
+    tag.chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+fn parse_attrs(mut rest: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    loop {
+        // A slash between attributes is a separator like a whitespace, e.g. `<svg/onload=...>`.
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            return attrs;
+        }
+        let key_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+
+        let value = if let Some(after_eq) = rest.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            match after_eq.chars().next() {
+                Some(q) if q == '"' || q == '\'' => {
+                    let body = &after_eq[1..];
+                    let end = body.find(q).unwrap_or(body.len());
+                    rest = body.get(end + 1..).unwrap_or("");
+                    &body[..end]
+                }
+                _ => {
+                    let end = after_eq.find(char::is_whitespace).unwrap_or(after_eq.len());
+                    rest = &after_eq[end..];
+                    &after_eq[..end]
+                }
+            }
+        } else {
+            ""
+        };
+        if !key.is_empty() {
+            attrs.push((key, decode_entities(value)));
+        }
+    }
+}
+
+pub(crate) fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        let after = &rest[amp + 1..];
+        let entity = after
+            .find(';')
+            .filter(|&semi| semi <= 10)
+            .and_then(|semi| decode_entity(&after[..semi]).map(|c| (c, semi)));
+        match entity {
+            Some((c, semi)) => {
+                decoded.push(c);
+                rest = &after[semi + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = after;
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let code = if let Some(hex) = entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                u32::from_str_radix(hex, 16).ok()?
+            } else {
+                entity.strip_prefix('#')?.parse().ok()?
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Mode {
+    Text,
+    Markdown,
+}
+
+enum List {
+    Unordered,
+    Ordered(u32),
+}
+
+struct Link {
+    href: Option<String>,
+    start: usize,
+}
+
+struct Renderer {
+    mode: Mode,
+    out: String,
+    lists: Vec<List>,
+    links: Vec<Link>,
+    quote_depth: usize,
+    pre_depth: usize,
+    code_depth: usize,
+    dropping: Option<String>,
+    table_cell: usize,
+    table_row: usize,
+}
+
+impl Renderer {
+    fn new(mode: Mode) -> Self {
+        Renderer {
+            mode,
+            out: String::new(),
+            lists: Vec::new(),
+            links: Vec::new(),
+            quote_depth: 0,
+            pre_depth: 0,
+            code_depth: 0,
+            dropping: None,
+            table_cell: 0,
+            table_row: 0,
+        }
+    }
+
+    fn markdown(&self) -> bool {
+        self.mode == Mode::Markdown
+    }
+
+    fn render(mut self, html: &str) -> String {
+        for token in Tokenizer::new(html) {
+            if let Some(dropping) = &self.dropping {
+                if matches!(&token, Token::End { name } if name == dropping) {
+                    self.dropping = None;
+                }
+                continue;
+            }
+            match token {
+                Token::Text(text) => self.text(&decode_entities(text)),
+                Token::Start {
+                    name,
+                    attrs,
+                    self_closing,
+                } => {
+                    if DROPPED_WITH_CONTENTS.contains(&name.as_str()) {
+                        if !self_closing && !VOID_ELEMENTS.contains(&name.as_str()) {
+                            self.dropping = Some(name);
+                        }
+                        continue;
+                    }
+                    self.start(&name, &attrs);
+                }
+                Token::End { name } => self.end(&name),
+            }
+        }
+        finish(&self.out)
+    }
+
+    fn start(&mut self, name: &str, attrs: &[(String, String)]) {
+        let attr = |key: &str| {
+            attrs
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.trim().to_string())
+        };
+        match name {
+            "p" | "div" | "section" | "article" | "table" => self.block_break(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block_break();
+                if self.markdown() {
+                    let level = name[1..].parse().unwrap_or(1);
+                    self.push(&format!("{} ", "#".repeat(level)));
+                }
+            }
+            "br" => self.newline(),
+            "hr" => {
+                self.block_break();
+                self.push(if self.markdown() { "---" } else { "----" });
+                self.block_break();
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.block_break();
+                }
+                self.lists.push(if name == "ol" {
+                    List::Ordered(0)
+                } else {
+                    List::Unordered
+                });
+            }
+            "li" => {
+                self.line_break();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(List::Ordered(n)) => {
+                        *n += 1;
+                        format!("{}. ", n)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.push(&format!("{}{}", indent, marker));
+            }
+            "blockquote" => {
+                self.block_break();
+                self.quote_depth += 1;
+            }
+            "pre" => {
+                self.block_break();
+                if self.markdown() {
+                    self.push("```\n");
+                }
+                self.pre_depth += 1;
+            }
+            "code" if self.markdown() && self.pre_depth == 0 => {
+                self.code_depth += 1;
+                self.push("`");
+            }
+            "strong" | "b" if self.markdown() => self.push("**"),
+            "em" | "i" if self.markdown() => self.push("_"),
+            "a" => {
+                let href = attr("href").filter(|h| !h.is_empty() && is_allowed_url(h));
+                self.links.push(Link {
+                    href,
+                    start: self.out.len(),
+                });
+            }
+            "img" => {
+                let alt = attr("alt").unwrap_or_default();
+                match attr("src").filter(|s| is_allowed_url(s)) {
+                    Some(src) if self.markdown() => self.push(&format!("![{}]({})", alt, src)),
+                    _ if !alt.is_empty() => self.push(&alt),
+                    _ => {}
+                }
+            }
+            "tr" => {
+                self.line_break();
+                self.table_cell = 0;
+                if self.markdown() {
+                    self.push("|");
+                }
+            }
+            "td" | "th" => {
+                self.trim_trailing_spaces();
+                if self.markdown() {
+                    self.push(" ");
+                } else if self.table_cell > 0 {
+                    self.push(" | ");
+                }
+                self.table_cell += 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        match name {
+            "p" | "div" | "section" | "article" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block_break()
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block_break();
+                }
+            }
+            "li" => self.line_break(),
+            "blockquote" => {
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                self.block_break();
+            }
+            "pre" => {
+                self.pre_depth = self.pre_depth.saturating_sub(1);
+                if self.markdown() {
+                    self.line_break();
+                    self.push("```");
+                }
+                self.block_break();
+            }
+            "code" if self.markdown() && self.pre_depth == 0 => {
+                self.code_depth = self.code_depth.saturating_sub(1);
+                self.push("`");
+            }
+            "strong" | "b" if self.markdown() => self.push("**"),
+            "em" | "i" if self.markdown() => self.push("_"),
+            "a" => self.end_link(),
+            "td" | "th" if self.markdown() => self.push(" |"),
+            "tr" => {
+                self.table_row += 1;
+                if self.markdown() && self.table_row == 1 {
+                    let separator = format!("\n|{}", " --- |".repeat(self.table_cell));
+                    self.push(&separator);
+                }
+            }
+            "table" => {
+                self.table_row = 0;
+                self.block_break();
+            }
+            _ => {}
+        }
+    }
+
+    fn end_link(&mut self) {
+        let link = match self.links.pop() {
+            Some(link) => link,
+            None => return,
+        };
+        let href = match link.href {
+            Some(href) => href,
+            None => return,
+        };
+        let text = self.out[link.start..].trim().to_string();
+        self.out.truncate(link.start);
+        let rendered = match self.mode {
+            Mode::Markdown if text.is_empty() || text == href => format!("<{}>", href),
+            Mode::Markdown => format!("[{}]({})", text, href),
+            Mode::Text if text.is_empty() || text == href => href,
+            Mode::Text => format!("{} ({})", text, href),
+        };
+        self.push(&rendered);
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.pre_depth > 0 {
+            for (i, line) in text.split('\n').enumerate() {
+                if i > 0 {
+                    self.newline();
+                }
+                self.push(line);
+            }
+            return;
+        }
+        let mut collapsed = String::with_capacity(text.len());
+        for (i, word) in text.split_whitespace().enumerate() {
+            if i > 0 {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+        }
+        let starts_with_space = text.starts_with(char::is_whitespace);
+        let ends_with_space = text.ends_with(char::is_whitespace);
+        if collapsed.is_empty() {
+            if starts_with_space && !self.at_line_start() && !self.out.ends_with(' ') {
+                self.out.push(' ');
+            }
+            return;
+        }
+        if starts_with_space && !self.at_line_start() && !self.out.ends_with(' ') {
+            self.out.push(' ');
+        }
+        if self.markdown() && self.code_depth == 0 {
+            collapsed = escape_markdown(&collapsed);
+        }
+        self.push(&collapsed);
+        if ends_with_space {
+            self.out.push(' ');
+        }
+    }
+
+    /// Pushes the string, prefixing the quote markers at the start of a line.
+    fn push(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        if self.at_line_start() && self.quote_depth > 0 {
+            let marker = if self.markdown() { "> " } else { "| " };
+            self.out.push_str(&marker.repeat(self.quote_depth));
+        }
+        self.out.push_str(s);
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn newline(&mut self) {
+        self.trim_trailing_spaces();
+        self.out.push('\n');
+    }
+
+    fn line_break(&mut self) {
+        if !self.at_line_start() {
+            self.newline();
+        }
+    }
+
+    fn block_break(&mut self) {
+        if self.out.is_empty() {
+            return;
+        }
+        self.line_break();
+        if !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn trim_trailing_spaces(&mut self) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+    }
+}
+
+/// Escapes the characters that would be interpreted as Markdown syntax in a text.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Trims trailing spaces of each line and collapses three or more line breaks into a blank line.
+fn finish(out: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in out.lines().map(str::trim_end) {
+        if line.is_empty() && lines.last().map_or(true, |l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::response::Event;
+
+    use super::{sanitize, to_markdown, to_text, Token, Tokenizer};
+
+    const RUST_TOKYO: &str = include_str!("../tests/fixtures/descriptions/rust_tokyo.html");
+    const RUST_TOKYO_TEXT: &str = include_str!("../tests/fixtures/descriptions/rust_tokyo.txt");
+    const RUST_TOKYO_MARKDOWN: &str = include_str!("../tests/fixtures/descriptions/rust_tokyo.md");
+    const MOKUMOKU: &str = include_str!("../tests/fixtures/descriptions/mokumoku.html");
+    const MOKUMOKU_TEXT: &str = include_str!("../tests/fixtures/descriptions/mokumoku.txt");
+    const MOKUMOKU_MARKDOWN: &str = include_str!("../tests/fixtures/descriptions/mokumoku.md");
+
+    #[test]
+    fn test_tokenizer() {
+        let tokens: Vec<Token> =
+            Tokenizer::new("<a href='x>y' target=_blank>link</a><!-- c --><br/>").collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Start {
+                    name: "a".to_string(),
+                    attrs: vec![
+                        ("href".to_string(), "x>y".to_string()),
+                        ("target".to_string(), "_blank".to_string())
+                    ],
+                    self_closing: false,
+                },
+                Token::Text("link"),
+                Token::End {
+                    name: "a".to_string()
+                },
+                Token::Start {
+                    name: "br".to_string(),
+                    attrs: vec![],
+                    self_closing: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_raw_text() {
+        let tokens: Vec<Token> =
+            Tokenizer::new("<script>if (a < b) { x('</p>') }</SCRIPT>ok").collect();
+        assert_eq!(tokens[1], Token::Text("if (a < b) { x('</p>') }"));
+        assert_eq!(tokens[3], Token::Text("ok"));
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(
+            sanitize(
+                "<p onclick=\"x()\">Hi<script>alert(1)</script>\
+                 <iframe src=\"https://www.youtube.com/embed/x\"></iframe>\
+                 <a href=\"javascript:alert(1)\">a</a><a href=\"/event/\">b</a></p>"
+            ),
+            "<p>Hi<a>a</a><a href=\"/event/\">b</a></p>"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_keeps_allowed_elements_only() {
+        assert_eq!(
+            sanitize(
+                "<svg><a href=\"https://example.com/\">x</a></svg>\
+                 <math><mi>y</mi></math>\
+                 <form action=\"https://example.com/\"><input name=\"q\"><button>Go</button></form>\
+                 <object data=\"x.swf\">z</object><marquee title=\"m\">Hi</marquee><P>ok</P>"
+            ),
+            "GoHi<p>ok</p>"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_slash_separated_attrs() {
+        assert_eq!(sanitize("<svg/onload=alert(1)>"), "");
+        assert_eq!(sanitize("<a/onclick=\"x()\">a</a>"), "<a>a</a>");
+        assert_eq!(
+            sanitize("<img src=x/onerror=alert(1)>"),
+            "<img src=\"x/onerror=alert(1)\">"
+        );
+        assert_eq!(
+            sanitize("<a href=\"/event/\"/title=\"t\" data-x=\"1\">a</a>"),
+            "<a href=\"/event/\" title=\"t\">a</a>"
+        );
+    }
+
+    #[test]
+    fn test_to_text_fixtures() {
+        assert_eq!(to_text(RUST_TOKYO), RUST_TOKYO_TEXT.trim_end());
+        assert_eq!(to_text(MOKUMOKU), MOKUMOKU_TEXT.trim_end());
+    }
+
+    #[test]
+    fn test_to_markdown_fixtures() {
+        assert_eq!(to_markdown(RUST_TOKYO), RUST_TOKYO_MARKDOWN.trim_end());
+        assert_eq!(to_markdown(MOKUMOKU), MOKUMOKU_MARKDOWN.trim_end());
+    }
+
+    #[test]
+    fn test_to_markdown_escapes_text() {
+        assert_eq!(to_markdown("<p>a_b *c*</p>"), "a\\_b \\*c\\*");
+    }
+
+    #[test]
+    fn test_event_description() {
+        let event: Event = serde_json::from_value(json!({
+            "event_id": 1,
+            "description": "<h2>Rust</h2><p>Hello <a href=\"https://www.rust-lang.org/\">Rust</a></p>",
+        }))
+        .unwrap();
+        assert_eq!(
+            event.description_text().unwrap(),
+            "Rust\n\nHello Rust (https://www.rust-lang.org/)"
+        );
+        assert_eq!(
+            event.description_markdown().unwrap(),
+            "## Rust\n\nHello [Rust](https://www.rust-lang.org/)"
+        );
+    }
+}
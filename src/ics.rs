@@ -0,0 +1,323 @@
+//! Converts events into iCalendar format along with RFC 5545.
+//! This module is available when `ics` feature is enabled.
+//!
+//! Each `Event` becomes a VEVENT whose UID is derived from `event_id`, so importing the same event twice
+//! updates the existing entry instead of duplicating it.
+//! Since DTSTART is mandatory in VEVENT, events without a valid `started_at` are skipped.
+
+use chrono::{DateTime, Utc};
+
+use crate::response::{ConnpassResponse, Event};
+
+const CRLF: &str = "\r\n";
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Converts the events in the response into a VCALENDAR.
+pub fn from_response(res: &ConnpassResponse) -> String {
+    vcalendar(res.events())
+}
+
+/// Converts the events into a VCALENDAR.
+///
+/// # Example:
+/// ```no_run
+/// use connpass_rs::{client::ConnpassClient, ics, query::builder::QueryBuilder};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let query = QueryBuilder::begin().keyword("Rust").build().unwrap();
+///     let res = ConnpassClient::new().send_request(query).await.unwrap();
+///     std::fs::write("events.ics", ics::vcalendar(res.events())).unwrap();
+/// }
+/// ```
+pub fn vcalendar<'a>(events: impl IntoIterator<Item = &'a Event>) -> String {
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(
+        &mut ics,
+        &format!(
+            "PRODID:-//connpass-rs//connpass-rs {}//EN",
+            env!("CARGO_PKG_VERSION")
+        ),
+    );
+    push_line(&mut ics, "CALSCALE:GREGORIAN");
+    for event in events {
+        if let Some(vevent) = vevent(event) {
+            ics.push_str(&vevent);
+        }
+    }
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+/// Converts the event into a VEVENT. The lines are folded and terminated by CRLF.
+/// Returns `None` if the event doesn't have a valid `started_at`.
+pub fn vevent(event: &Event) -> Option<String> {
+    let started_at = event.started_at().and_then(to_utc)?;
+    let updated_at = event.updated_at().and_then(to_utc);
+
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN:VEVENT");
+    push_line(&mut ics, &format!("UID:{}", uid(event)));
+    push_line(
+        &mut ics,
+        &format!("DTSTAMP:{}", format_utc(updated_at.unwrap_or(started_at))),
+    );
+    push_line(&mut ics, &format!("DTSTART:{}", format_utc(started_at)));
+    if let Some(ended_at) = event.ended_at().and_then(to_utc) {
+        push_line(&mut ics, &format!("DTEND:{}", format_utc(ended_at)));
+    }
+    if let Some(updated_at) = updated_at {
+        push_line(
+            &mut ics,
+            &format!("LAST-MODIFIED:{}", format_utc(updated_at)),
+        );
+    }
+    if let Some(title) = event.title() {
+        push_line(&mut ics, &format!("SUMMARY:{}", escape_text(title)));
+    }
+    if let Some(description) = summary_description(event) {
+        push_line(
+            &mut ics,
+            &format!("DESCRIPTION:{}", escape_text(&description)),
+        );
+    }
+    if let Some(description) = event.description() {
+        push_line(
+            &mut ics,
+            &format!("X-ALT-DESC;FMTTYPE=text/html:{}", escape_text(description)),
+        );
+    }
+    if let Some(location) = location(event) {
+        push_line(&mut ics, &format!("LOCATION:{}", escape_text(&location)));
+    }
+    if let Some((lat, lon)) = geo(event) {
+        push_line(&mut ics, &format!("GEO:{};{}", lat, lon));
+    }
+    if let Some(url) = event.event_url() {
+        push_line(&mut ics, &format!("URL:{}", url));
+    }
+    push_line(&mut ics, "END:VEVENT");
+    Some(ics)
+}
+
+fn uid(event: &Event) -> String {
+    format!("{}@connpass.com", event.id())
+}
+
+fn to_utc(datetime: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(datetime)
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+fn format_utc(datetime: DateTime<Utc>) -> String {
+    datetime.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Makes the plain text DESCRIPTION from `catch`, `description` converted into plain text, and `event_url`.
+/// The HTML of `description` itself goes to X-ALT-DESC.
+fn summary_description(event: &Event) -> Option<String> {
+    let description = event.description().map(plain_text);
+    let parts: Vec<&str> = [event.catch(), description.as_deref(), event.event_url()]
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|s| !s.is_empty())
+        .collect();
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("\n\n"))
+    }
+}
+
+#[cfg(feature = "html")]
+fn plain_text(html: &str) -> String {
+    crate::html::to_text(html)
+}
+
+/// Without `html` feature, the tags are stripped with line breaks for blocks, and the common entities are decoded.
+#[cfg(not(feature = "html"))]
+fn plain_text(html: &str) -> String {
+    const BLOCKS: [&str; 11] = [
+        "br", "p", "div", "li", "tr", "h1", "h2", "h3", "h4", "h5", "h6",
+    ];
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('>')
+            .map_or(rest.len(), |i| start + i + 1);
+        let name: String = rest[start + 1..end]
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+        if BLOCKS.contains(&name.to_ascii_lowercase().as_str()) {
+            text.push('\n');
+        }
+        rest = &rest[end..];
+    }
+    text.push_str(rest);
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn location(event: &Event) -> Option<String> {
+    let parts: Vec<&str> = [event.place(), event.address()]
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|s| !s.is_empty())
+        .collect();
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(", "))
+    }
+}
+
+fn geo(event: &Event) -> Option<(f64, f64)> {
+    let lat = event.lat()?.parse().ok()?;
+    let lon = event.lon()?.parse().ok()?;
+    Some((lat, lon))
+}
+
+/// Escapes a TEXT value. See RFC 5545 section 3.3.11.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Pushes a content line folded at 75 octets without splitting multi-byte characters.
+/// See RFC 5545 section 3.1.
+fn push_line(ics: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        // Continuation lines start with a space, which counts toward the limit.
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            ics.push_str(CRLF);
+            ics.push(' ');
+            octets = 1;
+        }
+        ics.push(c);
+        octets += c.len_utf8();
+    }
+    ics.push_str(CRLF);
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::response::Event;
+
+    use super::{escape_text, push_line, vcalendar, vevent};
+
+    fn event() -> Event {
+        serde_json::from_value(json!({
+            "event_id": 228732,
+            "title": "Rust.Tokyo 2021",
+            "catch": "Rust, Tokyo; and you",
+            "description": "<p>Rust.Tokyo</p><p>Rust &amp; Tokyo</p>",
+            "event_url": "https://rust.connpass.com/event/228732/",
+            "started_at": "2021-11-12T19:30:00+09:00",
+            "ended_at": "2021-11-13T01:00:00+09:00",
+            "place": "オンライン",
+            "address": "東京都渋谷区",
+            "lat": "35.658034",
+            "lon": "139.701636",
+            "updated_at": "2021-10-20T12:00:00+09:00",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(
+            escape_text("a,b;c\\d\r\ne"),
+            "a\\,b\\;c\\\\d\\ne".to_string()
+        );
+    }
+
+    #[test]
+    fn test_push_line_folding() {
+        let mut ics = String::new();
+        push_line(&mut ics, &"a".repeat(80));
+        assert_eq!(ics, format!("{}\r\n {}\r\n", "a".repeat(75), "a".repeat(5)));
+
+        // "あ" is 3 octets, so the first line holds 25 characters and a continuation line holds 24.
+        let mut ics = String::new();
+        push_line(&mut ics, &"あ".repeat(50));
+        let lines: Vec<&str> = ics.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(lines[0], "あ".repeat(25));
+        assert_eq!(lines[1], format!(" {}", "あ".repeat(24)));
+        assert_eq!(lines[2], " あ");
+        assert!(lines.iter().all(|l| l.len() <= 75));
+    }
+
+    #[test]
+    fn test_vevent() {
+        let vevent = vevent(&event()).unwrap();
+        let lines: Vec<&str> = vevent.split("\r\n").collect();
+        assert_eq!(lines[0], "BEGIN:VEVENT");
+        assert!(lines.contains(&"UID:228732@connpass.com"));
+        assert!(lines.contains(&"DTSTAMP:20211020T030000Z"));
+        assert!(lines.contains(&"DTSTART:20211112T103000Z"));
+        assert!(lines.contains(&"DTEND:20211112T160000Z"));
+        assert!(lines.contains(&"SUMMARY:Rust.Tokyo 2021"));
+        assert!(lines.contains(&"LOCATION:オンライン\\, 東京都渋谷区"));
+        assert!(lines.contains(&"GEO:35.658034;139.701636"));
+        assert!(lines.contains(&"URL:https://rust.connpass.com/event/228732/"));
+        assert!(vevent.ends_with("END:VEVENT\r\n"));
+    }
+
+    #[test]
+    fn test_vevent_description() {
+        let vevent = vevent(&event()).unwrap().replace("\r\n ", "");
+        let lines: Vec<&str> = vevent.split("\r\n").collect();
+        assert!(lines.contains(
+            &"DESCRIPTION:Rust\\, Tokyo\\; and you\\n\\nRust.Tokyo\\n\\nRust & Tokyo\\n\\nhttps://rust.connpass.com/event/228732/"
+        ));
+        assert!(lines
+            .contains(&"X-ALT-DESC;FMTTYPE=text/html:<p>Rust.Tokyo</p><p>Rust &amp\\; Tokyo</p>"));
+    }
+
+    #[test]
+    fn test_vevent_without_started_at() {
+        let event: Event = serde_json::from_value(json!({ "event_id": 1 })).unwrap();
+        assert_eq!(vevent(&event), None);
+    }
+
+    #[test]
+    fn test_vcalendar() {
+        let events = vec![event()];
+        let ics = vcalendar(&events);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.contains("BEGIN:VEVENT\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+}
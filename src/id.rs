@@ -0,0 +1,102 @@
+//! Typed identifiers, so that e.g. a series id can't be passed where an event id is expected.
+//! Each of them is (de)serialized as a bare number, and formatted and parsed as a decimal number.
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{ConnpassCliError, ConnpassResult, ValidationError};
+
+macro_rules! define_id {
+    ($(#[$meta:meta])* $name:ident, $what:literal) => {
+        $(#[$meta])*
+        #[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+        #[serde(transparent)]
+        pub struct $name(u32);
+
+        impl $name {
+            pub const fn new(id: u32) -> Self {
+                $name(id)
+            }
+
+            pub const fn get(self) -> u32 {
+                self.0
+            }
+        }
+
+        impl From<u32> for $name {
+            fn from(id: u32) -> Self {
+                $name(id)
+            }
+        }
+
+        impl From<$name> for u32 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ConnpassCliError;
+
+            fn from_str(s: &str) -> ConnpassResult<Self> {
+                s.parse().map($name).map_err(|_| {
+                    ConnpassCliError::Validation(ValidationError::InvalidToken {
+                        msg: format!("Invalid {}: {}", $what, s),
+                    })
+                })
+            }
+        }
+    };
+}
+
+define_id!(
+    /// The id of an event, e.g. `228732` of `https://rust.connpass.com/event/228732/`.
+    EventId,
+    "event id"
+);
+define_id!(
+    /// The id of a series, which is a group of events on connpass.
+    SeriesId,
+    "series id"
+);
+define_id!(
+    /// The id of a user, e.g. the owner of an event.
+    UserId,
+    "user id"
+);
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{EventId, SeriesId};
+
+    #[test]
+    fn test_parse_and_display() {
+        let id: EventId = "228732".parse().unwrap();
+        assert_eq!(id, EventId::new(228732));
+        assert_eq!(id.to_string(), "228732");
+        assert_eq!(format!("{:<8}|", id), "228732  |");
+        assert!("rust".parse::<EventId>().is_err());
+        assert!("-1".parse::<SeriesId>().is_err());
+    }
+
+    #[test]
+    fn test_serde_as_number() {
+        assert_eq!(
+            serde_json::to_value(SeriesId::new(4424)).unwrap(),
+            json!(4424)
+        );
+        assert_eq!(
+            serde_json::from_value::<EventId>(json!(228732)).unwrap(),
+            EventId::new(228732)
+        );
+    }
+}
@@ -1,6 +1,25 @@
 //! The API client for quering events what you're looking for in connpass.com written in Rust.
 
 pub mod client;
+#[cfg(feature = "config")]
+pub mod config;
 pub mod errors;
+#[cfg(feature = "export")]
+pub mod export;
+#[cfg(feature = "feed")]
+pub mod feed;
+#[cfg(feature = "html")]
+pub mod html;
+#[cfg(feature = "ics")]
+pub mod ics;
+pub mod id;
+#[cfg(feature = "materials")]
+pub mod materials;
+pub mod multi;
 pub mod query;
+pub mod resolver;
 pub mod response;
+pub mod scheduler;
+pub mod seen;
+pub mod url;
+pub mod watch;
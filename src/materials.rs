@@ -0,0 +1,384 @@
+//! Extracts links (slides, videos, etc.) and hashtags from events.
+//! This module is available when `html` feature is enabled.
+//!
+//! Links are collected from `href` and `src` attributes and bare URLs in `Event::description`, and from `Event::event_url`.
+//! Hashtags are collected from `Event::hash_tag` and `#tag`s in the description.
+//! Both are de-duplicated, keeping the order of their first appearance.
+
+use std::collections::HashSet;
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    html::{decode_entities, Token, Tokenizer},
+    response::Event,
+};
+
+/// Where a link points to.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Provider {
+    SpeakerDeck,
+    SlideShare,
+    /// Google Docs, Slides and Sheets.
+    Docs,
+    YouTube,
+    Connpass,
+    Other,
+}
+
+impl Provider {
+    fn of(url: &Url) -> Self {
+        let host = url.host_str().unwrap_or_default();
+        let is = |domain: &str| host == domain || host.ends_with(&format!(".{}", domain));
+        if is("speakerdeck.com") {
+            Provider::SpeakerDeck
+        } else if is("slideshare.net") {
+            Provider::SlideShare
+        } else if host == "docs.google.com" {
+            Provider::Docs
+        } else if is("youtube.com") || host == "youtu.be" {
+            Provider::YouTube
+        } else if is("connpass.com") {
+            Provider::Connpass
+        } else {
+            Provider::Other
+        }
+    }
+
+    /// Returns `true` for the providers hosting slides.
+    pub fn is_slide(&self) -> bool {
+        matches!(
+            self,
+            Provider::SpeakerDeck | Provider::SlideShare | Provider::Docs
+        )
+    }
+
+    /// Returns `true` for the providers hosting videos.
+    pub fn is_video(&self) -> bool {
+        matches!(self, Provider::YouTube)
+    }
+}
+
+/// A link categorized by its provider.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Link {
+    url: String,
+    provider: Provider,
+}
+
+impl Link {
+    /// Parses the URL and categorizes it. Returns `None` unless it is an absolute http(s) URL.
+    /// YouTube URLs are normalized into `https://www.youtube.com/watch?v={id}`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut url = Url::parse(s.trim()).ok()?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return None;
+        }
+        url.set_fragment(None);
+        let provider = Provider::of(&url);
+        let url = match provider {
+            Provider::YouTube => youtube_watch_url(&url).unwrap_or_else(|| url.to_string()),
+            _ => url.to_string(),
+        };
+        Some(Link { url, provider })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn provider(&self) -> Provider {
+        self.provider
+    }
+
+    /// The key for de-duplication, which ignores the scheme, "www." and a trailing slash.
+    fn key(&self) -> String {
+        let without_scheme = self
+            .url
+            .trim_start_matches("https://")
+            .trim_start_matches("http://");
+        without_scheme
+            .trim_start_matches("www.")
+            .trim_end_matches('/')
+            .to_string()
+    }
+}
+
+/// Links and hashtags extracted from an event.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub struct Materials {
+    links: Vec<Link>,
+    hashtags: Vec<String>,
+}
+
+impl Materials {
+    /// Returns all the links.
+    pub fn links(&self) -> &[Link] {
+        &self.links
+    }
+
+    /// Returns the links to slides.
+    pub fn slides(&self) -> impl Iterator<Item = &Link> {
+        self.links.iter().filter(|l| l.provider.is_slide())
+    }
+
+    /// Returns the links to videos.
+    pub fn videos(&self) -> impl Iterator<Item = &Link> {
+        self.links.iter().filter(|l| l.provider.is_video())
+    }
+
+    /// Returns the hashtags without leading "#".
+    pub fn hashtags(&self) -> &[String] {
+        &self.hashtags
+    }
+
+    fn push_link(&mut self, seen: &mut HashSet<String>, s: &str) {
+        if let Some(link) = Link::parse(s) {
+            if seen.insert(link.key()) {
+                self.links.push(link);
+            }
+        }
+    }
+
+    fn push_hashtag(&mut self, seen: &mut HashSet<String>, tag: &str) {
+        let tag = tag.trim().trim_start_matches(['#', '＃']);
+        if is_hashtag(tag) && seen.insert(tag.to_lowercase()) {
+            self.hashtags.push(tag.to_string());
+        }
+    }
+}
+
+impl Event {
+    /// Extracts the links and hashtags from the event.
+    pub fn materials(&self) -> Materials {
+        extract(self)
+    }
+}
+
+/// Extracts the links and hashtags from the event.
+pub fn extract(event: &Event) -> Materials {
+    let mut materials = Materials::default();
+    let mut seen_links = HashSet::new();
+    let mut seen_hashtags = HashSet::new();
+
+    if let Some(hash_tag) = event.hash_tag() {
+        materials.push_hashtag(&mut seen_hashtags, hash_tag);
+    }
+
+    let mut in_raw_text = false;
+    for token in Tokenizer::new(event.description().unwrap_or_default()) {
+        match token {
+            Token::Start { name, attrs, .. } => {
+                in_raw_text = name == "script" || name == "style";
+                for (key, value) in attrs {
+                    if key == "href" || key == "src" {
+                        materials.push_link(&mut seen_links, &value);
+                    }
+                }
+            }
+            Token::End { .. } => in_raw_text = false,
+            Token::Text(_) if in_raw_text => {}
+            Token::Text(text) => {
+                let text = decode_entities(text);
+                for url in bare_urls(&text) {
+                    materials.push_link(&mut seen_links, url);
+                }
+                for tag in hashtags(&text) {
+                    materials.push_hashtag(&mut seen_hashtags, tag);
+                }
+            }
+        }
+    }
+
+    if let Some(event_url) = event.event_url() {
+        materials.push_link(&mut seen_links, event_url);
+    }
+    materials
+}
+
+/// Finds URLs written as plain text. A URL ends at a whitespace or a non-ASCII character,
+/// since URLs in Japanese sentences are often followed by text without spaces.
+fn bare_urls(text: &str) -> Vec<&str> {
+    let mut urls = Vec::new();
+    let mut rest = text;
+    while let Some(start) = ["http://", "https://"]
+        .iter()
+        .filter_map(|scheme| rest.find(scheme))
+        .min()
+    {
+        let candidate = &rest[start..];
+        let end = candidate
+            .find(|c: char| !c.is_ascii_graphic() || matches!(c, '"' | '<' | '>'))
+            .unwrap_or(candidate.len());
+        let url = candidate[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']']);
+        urls.push(url);
+        rest = &candidate[end..];
+    }
+    urls
+}
+
+/// Finds `#tag`s at the start of the text or after a non-word character, e.g. a whitespace or "、".
+/// "/" and "&" don't start a hashtag, so that fragments of URLs are not hashtags.
+fn hashtags(text: &str) -> Vec<&str> {
+    let mut tags = Vec::new();
+    let mut prev: Option<char> = None;
+    for (i, c) in text.char_indices() {
+        if matches!(c, '#' | '＃')
+            && prev.map_or(true, |p| !is_hashtag_char(p) && !matches!(p, '/' | '&'))
+        {
+            let body = &text[i + c.len_utf8()..];
+            let end = body
+                .find(|c: char| !is_hashtag_char(c))
+                .unwrap_or(body.len());
+            tags.push(&body[..end]);
+        }
+        prev = Some(c);
+    }
+    tags
+}
+
+fn is_hashtag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// A hashtag needs at least one non-digit, so that "#1" is not a hashtag.
+fn is_hashtag(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars().all(is_hashtag_char) && !tag.chars().all(|c| c.is_numeric())
+}
+
+fn youtube_watch_url(url: &Url) -> Option<String> {
+    let id = if url.host_str() == Some("youtu.be") {
+        url.path_segments()?.next().map(str::to_string)
+    } else {
+        let segments: Vec<&str> = url.path_segments()?.collect();
+        match segments.as_slice() {
+            ["watch"] => url
+                .query_pairs()
+                .find(|(k, _)| k == "v")
+                .map(|(_, v)| v.into_owned()),
+            ["embed", id] | ["live", id] | ["shorts", id] => Some(id.to_string()),
+            _ => None,
+        }
+    }?;
+    if id.is_empty() {
+        return None;
+    }
+    Some(format!("https://www.youtube.com/watch?v={}", id))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::response::Event;
+
+    use super::{bare_urls, hashtags, Link, Provider};
+
+    #[test]
+    fn test_link_parse() {
+        let cases = [
+            (
+                "https://speakerdeck.com/yuk1ty/rust",
+                "https://speakerdeck.com/yuk1ty/rust",
+                Provider::SpeakerDeck,
+            ),
+            (
+                "https://www.slideshare.net/x/y",
+                "https://www.slideshare.net/x/y",
+                Provider::SlideShare,
+            ),
+            (
+                "https://docs.google.com/presentation/d/abc/edit#slide=id.p",
+                "https://docs.google.com/presentation/d/abc/edit",
+                Provider::Docs,
+            ),
+            (
+                "https://youtu.be/abc123",
+                "https://www.youtube.com/watch?v=abc123",
+                Provider::YouTube,
+            ),
+            (
+                "https://www.youtube.com/embed/abc123?start=10",
+                "https://www.youtube.com/watch?v=abc123",
+                Provider::YouTube,
+            ),
+            (
+                "https://rust.connpass.com/event/1/",
+                "https://rust.connpass.com/event/1/",
+                Provider::Connpass,
+            ),
+            (
+                "https://www.rust-lang.org/",
+                "https://www.rust-lang.org/",
+                Provider::Other,
+            ),
+        ];
+        for (s, url, provider) in cases {
+            let link = Link::parse(s).unwrap();
+            assert_eq!(link.url(), url);
+            assert_eq!(link.provider(), provider);
+        }
+        assert!(Link::parse("mailto:info@example.com").is_none());
+        assert!(Link::parse("/event/1/").is_none());
+    }
+
+    #[test]
+    fn test_bare_urls() {
+        assert_eq!(
+            bare_urls(
+                "資料はhttps://speakerdeck.com/a/bです。(https://example.com/x).\nhttp://a.b"
+            ),
+            vec![
+                "https://speakerdeck.com/a/b",
+                "https://example.com/x",
+                "http://a.b"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hashtags() {
+        assert_eq!(
+            hashtags("#rust_jp と ＃もくもく会、#1 a#b /#top"),
+            vec!["rust_jp", "もくもく会", "1"]
+        );
+    }
+
+    #[test]
+    fn test_materials() {
+        let event: Event = serde_json::from_value(json!({
+            "event_id": 1,
+            "event_url": "https://rust.connpass.com/event/1/",
+            "hash_tag": "rusttokyo",
+            "description": "<p>資料: <a href=\"https://speakerdeck.com/a/b\">https://speakerdeck.com/a/b</a></p>\
+                <iframe src=\"https://www.youtube.com/embed/abc123\"></iframe>\
+                <p>録画 https://youtu.be/abc123 と http://speakerdeck.com/a/b/</p>\
+                <p>#RustTokyo #rust_jp</p>\
+                <script>var u = \"https://example.com/tracker\";</script>\
+                <p><a href=\"https://docs.google.com/presentation/d/x\">slides</a>\
+                <a href=\"https://rust.connpass.com/event/1/\">event</a></p>",
+        }))
+        .unwrap();
+
+        let materials = event.materials();
+        let urls: Vec<(&str, Provider)> = materials
+            .links()
+            .iter()
+            .map(|l| (l.url(), l.provider()))
+            .collect();
+        assert_eq!(
+            urls,
+            vec![
+                ("https://speakerdeck.com/a/b", Provider::SpeakerDeck),
+                ("https://www.youtube.com/watch?v=abc123", Provider::YouTube),
+                ("https://docs.google.com/presentation/d/x", Provider::Docs),
+                ("https://rust.connpass.com/event/1/", Provider::Connpass),
+            ]
+        );
+        assert_eq!(materials.slides().count(), 2);
+        assert_eq!(materials.videos().count(), 1);
+        assert_eq!(materials.hashtags(), ["rusttokyo", "rust_jp"]);
+    }
+}
@@ -0,0 +1,179 @@
+//! Runs many queries at once and merges their results into one list of events.
+//! Each event is annotated with the labels of the queries which matched it.
+
+use std::collections::HashMap;
+
+use futures::{stream, StreamExt, TryStreamExt};
+
+use crate::{
+    client::ConnpassClient,
+    errors::{ConnpassCliError, ConnpassResult},
+    id::EventId,
+    query::Query,
+    response::Event,
+};
+
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// A set of labeled queries, which are sent with bounded concurrency.
+///
+/// Every page of each query is fetched from the one specified by the query, like `ConnpassClient::pages`.
+/// The pages of a query are fetched one by one, so the concurrency bounds the number of requests sent at once.
+///
+/// # Example:
+/// ```no_run
+/// use connpass_rs::{client::ConnpassClient, connpass_query, multi::MultiQuery};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let events = MultiQuery::new()
+///         .query("rust", connpass_query! { keyword: "Rust", count: 100 }.unwrap())
+///         .query("tokyo", connpass_query! { keyword: "東京", count: 100 }.unwrap())
+///         .run(&ConnpassClient::new())
+///         .await
+///         .unwrap();
+///     for event in events {
+///         println!("{:?}: {:?}", event.labels(), event.event().title());
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultiQuery {
+    queries: Vec<(String, Query)>,
+    concurrency: usize,
+}
+
+impl Default for MultiQuery {
+    fn default() -> Self {
+        MultiQuery {
+            queries: Vec::new(),
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+}
+
+impl MultiQuery {
+    /// Initializes `MultiQuery`. At most 4 requests are sent at once by default.
+    pub fn new() -> Self {
+        MultiQuery::default()
+    }
+
+    /// Adds the query with the label, which is attached to the events matched by it.
+    pub fn query(mut self, label: impl Into<String>, query: Query) -> Self {
+        self.queries.push((label.into(), query));
+        self
+    }
+
+    /// Sets how many requests can be sent at once. 0 is treated as 1.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sends every query until its last page, and merges the events by `event_id`.
+    /// The events are in the order of the queries added and then of the results, and the labels are in the order of the queries.
+    /// If any of the requests fails, the error is returned.
+    pub async fn run(&self, client: &ConnpassClient) -> ConnpassResult<Vec<LabeledEvent>> {
+        let results: Vec<(String, Vec<Event>)> = stream::iter(self.queries.iter().cloned())
+            .map(|(label, query)| async move {
+                let events = client
+                    .pages(query)
+                    .try_fold(Vec::new(), |mut events, res| async move {
+                        events.extend(res.into_events());
+                        Ok(events)
+                    })
+                    .await?;
+                Ok::<_, ConnpassCliError>((label, events))
+            })
+            .buffered(self.concurrency)
+            .try_collect()
+            .await?;
+        Ok(merge(results))
+    }
+}
+
+/// An event in the result of `MultiQuery`, with the labels of the queries which matched it.
+#[derive(PartialEq, Clone, Debug)]
+pub struct LabeledEvent {
+    event: Event,
+    labels: Vec<String>,
+}
+
+impl LabeledEvent {
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    pub fn into_event(self) -> Event {
+        self.event
+    }
+}
+
+/// Merges the results of the labeled queries by `event_id`. The first one found is kept for each event.
+fn merge(results: Vec<(String, Vec<Event>)>) -> Vec<LabeledEvent> {
+    let mut merged: Vec<LabeledEvent> = Vec::new();
+    let mut positions: HashMap<EventId, usize> = HashMap::new();
+    for (label, events) in results {
+        for event in events {
+            match positions.get(&event.id()) {
+                Some(&i) => {
+                    let labels = &mut merged[i].labels;
+                    if !labels.contains(&label) {
+                        labels.push(label.clone());
+                    }
+                }
+                None => {
+                    positions.insert(event.id(), merged.len());
+                    merged.push(LabeledEvent {
+                        event,
+                        labels: vec![label.clone()],
+                    });
+                }
+            }
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::response::Event;
+
+    use super::merge;
+
+    fn event(id: u32) -> Event {
+        serde_json::from_value(json!({ "event_id": id, "title": format!("event {}", id) })).unwrap()
+    }
+
+    #[test]
+    fn test_merge() {
+        let merged = merge(vec![
+            ("rust".to_string(), vec![event(1), event(2)]),
+            ("tokyo".to_string(), vec![event(3), event(1)]),
+            ("rust".to_string(), vec![event(2)]),
+        ]);
+        let summary: Vec<(u32, Vec<&str>)> = merged
+            .iter()
+            .map(|e| {
+                (
+                    e.event().id().get(),
+                    e.labels().iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, vec!["rust", "tokyo"]),
+                (2, vec!["rust"]),
+                (3, vec!["tokyo"]),
+            ]
+        );
+    }
+}
@@ -1,23 +1,29 @@
 use helper::*;
 
-use crate::errors::ConnpassCliError;
+use crate::{
+    errors::{ConnpassCliError, FieldError, ValidationError, ValidationErrors},
+    id::{EventId, SeriesId},
+    url::EventUrl,
+};
 
 use super::{
-    types::{FetchCountRange, FormatJson},
-    validator::Validator,
+    types::{FetchCountRange, FormatJson, YearMonth, YearMonthDay},
+    validator::{Policies, Validator},
     OrderOption, Query,
 };
 
 /// Utility builder for building `query::Query`.
 pub struct QueryBuilder {
-    event_id: Option<Vec<u32>>,
+    event_id: Option<Vec<EventId>>,
+    event_url: Option<Vec<String>>,
     keyword: Option<Vec<String>>,
     keyword_or: Option<Vec<String>>,
     ym: Option<Vec<u32>>,
     ymd: Option<Vec<u32>>,
     nickname: Option<Vec<String>>,
     owner_nickname: Option<Vec<String>>,
-    series_id: Option<Vec<u32>>,
+    series_id: Option<Vec<SeriesId>>,
+    group_subdomain: Option<Vec<String>>,
     start: Option<u32>,
     order: Option<OrderOption>,
     count: Option<FetchCountRange>,
@@ -28,6 +34,7 @@ impl Default for QueryBuilder {
     fn default() -> Self {
         Self {
             event_id: None,
+            event_url: None,
             keyword: None,
             keyword_or: None,
             ym: None,
@@ -35,6 +42,7 @@ impl Default for QueryBuilder {
             nickname: None,
             owner_nickname: None,
             series_id: None,
+            group_subdomain: None,
             start: None,
             order: None,
             count: None,
@@ -56,16 +64,28 @@ impl QueryBuilder {
         QueryBuilder::default()
     }
 
-    pub fn event_ids(mut self, ids: Vec<u32>) -> Self {
+    pub fn event_ids(mut self, ids: Vec<EventId>) -> Self {
         self.event_id = Some(ids);
         self
     }
 
-    pub fn event_id(mut self, id: u32) -> Self {
+    pub fn event_id(mut self, id: EventId) -> Self {
         self.event_id = push_or_create(self.event_id, id);
         self
     }
 
+    /// Accepts event URLs like `https://rust.connpass.com/event/228732/` instead of ids.
+    /// The URLs are parsed in `build`, and the ids are added after the ones set by `event_id`.
+    pub fn event_urls(mut self, urls: Vec<String>) -> Self {
+        self.event_url = Some(urls);
+        self
+    }
+
+    pub fn event_url(mut self, url: impl Into<String>) -> Self {
+        self.event_url = push_or_create(self.event_url, url.into());
+        self
+    }
+
     pub fn keywords(mut self, keywords: Vec<String>) -> Self {
         self.keyword = Some(keywords);
         self
@@ -126,16 +146,28 @@ impl QueryBuilder {
         self
     }
 
-    pub fn series_ids(mut self, series_ids: Vec<u32>) -> Self {
+    pub fn series_ids(mut self, series_ids: Vec<SeriesId>) -> Self {
         self.series_id = Some(series_ids);
         self
     }
 
-    pub fn series_id(mut self, series_id: u32) -> Self {
+    pub fn series_id(mut self, series_id: SeriesId) -> Self {
         self.series_id = push_or_create(self.series_id, series_id);
         self
     }
 
+    /// Narrows down to the groups by their subdomains (e.g. `rust` of `rust.connpass.com`) instead of series ids.
+    /// The subdomains are resolved into series ids by the client at request time. See `resolver` module for details.
+    pub fn group_subdomains(mut self, subdomains: Vec<String>) -> Self {
+        self.group_subdomain = Some(subdomains);
+        self
+    }
+
+    pub fn group_subdomain(mut self, subdomain: impl Into<String>) -> Self {
+        self.group_subdomain = push_or_create(self.group_subdomain, subdomain.into());
+        self
+    }
+
     pub fn start(mut self, start: u32) -> Self {
         self.start = Some(start);
         self
@@ -160,12 +192,62 @@ impl QueryBuilder {
     /// The following checks run in this function:
     /// 1. validate the `count` value in range of 0 to 100.
     /// 2. validate if the `format` value is just "json".
+    /// 3. validate if the `event_url` values are event URLs of connpass.
+    /// 4. validate if the ids and `start` are not 0, and the strings like `keyword` are not empty.
+    /// 5. validate if the `ym` and `ymd` values are valid dates like 202110 and 20211030.
+    ///
+    /// Every check runs even if some of them fail, and the failures are returned together as `ValidationError::Fields`.
     ///
     /// These validation specifications are described in connpass's documentation.
     /// Please have a look at https://connpass.com/about/api/.
     pub fn build(self) -> Result<Query, ConnpassCliError> {
-        let mut query = Query {
-            event_id: self.event_id,
+        let mut errors = Vec::new();
+
+        let mut event_id = self.event_id;
+        for url in self.event_url.unwrap_or_default() {
+            if let Some(url) = check(&mut errors, "event_url", url.parse::<EventUrl>()) {
+                event_id = push_or_create(event_id, url.event_id());
+            }
+        }
+
+        for id in event_id.iter().flatten().filter(|id| id.get() == 0) {
+            errors.push(FieldError::new("event_id", not_zero("event_id", id)));
+        }
+        check_not_empty(&mut errors, "keyword", &self.keyword);
+        check_not_empty(&mut errors, "keyword_or", &self.keyword_or);
+        for ym in self.ym.iter().flatten() {
+            check(&mut errors, "ym", YearMonth(*ym).validate());
+        }
+        for ymd in self.ymd.iter().flatten() {
+            check(&mut errors, "ymd", YearMonthDay(*ymd).validate());
+        }
+        check_not_empty(&mut errors, "nickname", &self.nickname);
+        check_not_empty(&mut errors, "owner_nickname", &self.owner_nickname);
+        for id in self.series_id.iter().flatten().filter(|id| id.get() == 0) {
+            errors.push(FieldError::new("series_id", not_zero("series_id", id)));
+        }
+        check_not_empty(&mut errors, "group_subdomain", &self.group_subdomain);
+        if self.start == Some(0) {
+            errors.push(FieldError::new("start", not_zero("start", &0)));
+        }
+
+        let count = self
+            .count
+            .and_then(|count| check(&mut errors, "count", count.validate()))
+            .map(|count| count.0);
+        let format = self
+            .format
+            .and_then(|format| check(&mut errors, "format", format.validate()))
+            .map(|format| format.0);
+
+        if !errors.is_empty() {
+            return Err(ConnpassCliError::Validation(ValidationError::Fields(
+                ValidationErrors::new(errors),
+            )));
+        }
+
+        Ok(Query {
+            event_id,
             keyword: self.keyword,
             keyword_or: self.keyword_or,
             ym: self.ym,
@@ -173,24 +255,28 @@ impl QueryBuilder {
             nickname: self.nickname,
             owner_nickname: self.owner_nickname,
             series_id: self.series_id,
+            group_subdomain: self.group_subdomain,
             start: self.start,
             order: self.order,
-            ..Default::default()
-        };
-
-        if let Some(count) = self.count {
-            query.count = Some(count.validate()?.0);
-        }
-
-        if let Some(format) = self.format {
-            query.format = Some(format.validate()?.0);
-        }
+            count,
+            format,
+        })
+    }
 
+    /// Builds the query like `build`, and then checks it against the policies.
+    /// The policies are checked only if `build` succeeds.
+    pub fn build_with(self, policies: &Policies) -> Result<Query, ConnpassCliError> {
+        let query = self.build()?;
+        policies.validate(&query)?;
         Ok(query)
     }
 }
 
 mod helper {
+    use std::fmt::Display;
+
+    use crate::errors::{ConnpassCliError, ConnpassResult, FieldError, ValidationError};
+
     pub fn push_or_create<T>(source: Option<Vec<T>>, pushed: T) -> Option<Vec<T>> {
         match source {
             Some(mut xs) => {
@@ -200,24 +286,81 @@ mod helper {
             None => Some(vec![pushed]),
         }
     }
+
+    /// Records the error of the field if the result is an error.
+    pub fn check<T>(
+        errors: &mut Vec<FieldError>,
+        field: &'static str,
+        result: ConnpassResult<T>,
+    ) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                let err = match err {
+                    ConnpassCliError::Validation(err) => err,
+                    err => ValidationError::InvalidToken {
+                        msg: err.to_string(),
+                    },
+                };
+                errors.push(FieldError::new(field, err));
+                None
+            }
+        }
+    }
+
+    pub fn check_not_empty(
+        errors: &mut Vec<FieldError>,
+        field: &'static str,
+        values: &Option<Vec<String>>,
+    ) {
+        if values.iter().flatten().any(|value| value.trim().is_empty()) {
+            errors.push(FieldError::new(
+                field,
+                ValidationError::InvalidToken {
+                    msg: format!("`{}` should not be empty", field),
+                },
+            ));
+        }
+    }
+
+    pub fn not_zero(field: &str, value: &impl Display) -> ValidationError {
+        ValidationError::OutOfRange {
+            msg: format!("`{}` should be greater than 0: {}", field, value),
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::{
         errors::{ConnpassCliError, ValidationError},
+        id::{EventId, SeriesId},
         query::{types::OrderOption, Query},
     };
 
     use super::QueryBuilder;
 
+    /// Builds the query expecting it to fail, and returns the names of the invalid fields.
+    fn invalid_fields(builder: QueryBuilder) -> Vec<&'static str> {
+        match builder.build() {
+            Err(ConnpassCliError::Validation(ValidationError::Fields(errors))) => {
+                errors.errors().iter().map(|error| error.field()).collect()
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
     #[test]
     fn test_add_event_ids() {
-        let builder = QueryBuilder::begin().event_ids(vec![1, 2, 3]);
+        let builder = QueryBuilder::begin().event_ids(vec![
+            EventId::new(1),
+            EventId::new(2),
+            EventId::new(3),
+        ]);
         assert_eq!(
             builder.build().unwrap(),
             Query {
-                event_id: Some(vec![1, 2, 3]),
+                event_id: Some(vec![EventId::new(1), EventId::new(2), EventId::new(3)]),
                 ..Default::default()
             }
         );
@@ -225,11 +368,11 @@ mod test {
 
     #[test]
     fn test_add_event_id() {
-        let builder = QueryBuilder::begin().event_id(1);
+        let builder = QueryBuilder::begin().event_id(EventId::new(1));
         assert_eq!(
             builder.build().unwrap(),
             Query {
-                event_id: Some(vec![1]),
+                event_id: Some(vec![EventId::new(1)]),
                 ..Default::default()
             }
         );
@@ -237,16 +380,57 @@ mod test {
 
     #[test]
     fn test_call_multiple_time_event_id() {
-        let builder = QueryBuilder::begin().event_id(1).event_id(2).event_id(3);
+        let builder = QueryBuilder::begin()
+            .event_id(EventId::new(1))
+            .event_id(EventId::new(2))
+            .event_id(EventId::new(3));
+        assert_eq!(
+            builder.build().unwrap(),
+            Query {
+                event_id: Some(vec![EventId::new(1), EventId::new(2), EventId::new(3)]),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_add_event_url() {
+        let builder = QueryBuilder::begin()
+            .event_id(EventId::new(1))
+            .event_url("https://rust.connpass.com/event/228732/")
+            .event_url("https://connpass.com/event/228733/");
         assert_eq!(
             builder.build().unwrap(),
             Query {
-                event_id: Some(vec![1, 2, 3]),
+                event_id: Some(vec![
+                    EventId::new(1),
+                    EventId::new(228732),
+                    EventId::new(228733)
+                ]),
                 ..Default::default()
             }
         );
     }
 
+    #[test]
+    fn test_add_event_urls() {
+        let builder = QueryBuilder::begin()
+            .event_urls(vec!["https://rust.connpass.com/event/228732/".to_string()]);
+        assert_eq!(
+            builder.build().unwrap(),
+            Query {
+                event_id: Some(vec![EventId::new(228732)]),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_validation_event_url() {
+        let builder = QueryBuilder::begin().event_url("https://rust.connpass.com/");
+        assert_eq!(invalid_fields(builder), vec!["event_url"]);
+    }
+
     #[test]
     fn test_add_keywords() {
         let builder = QueryBuilder::begin().keywords(vec![
@@ -528,11 +712,15 @@ mod test {
 
     #[test]
     fn test_add_series_ids() {
-        let builder = QueryBuilder::begin().series_ids(vec![1, 2, 3]);
+        let builder = QueryBuilder::begin().series_ids(vec![
+            SeriesId::new(1),
+            SeriesId::new(2),
+            SeriesId::new(3),
+        ]);
         assert_eq!(
             builder.build().unwrap(),
             Query {
-                series_id: Some(vec![1, 2, 3]),
+                series_id: Some(vec![SeriesId::new(1), SeriesId::new(2), SeriesId::new(3)]),
                 ..Default::default()
             }
         );
@@ -540,11 +728,11 @@ mod test {
 
     #[test]
     fn test_add_series_id() {
-        let builder = QueryBuilder::begin().series_id(1);
+        let builder = QueryBuilder::begin().series_id(SeriesId::new(1));
         assert_eq!(
             builder.build().unwrap(),
             Query {
-                series_id: Some(vec![1]),
+                series_id: Some(vec![SeriesId::new(1)]),
                 ..Default::default()
             }
         );
@@ -552,11 +740,40 @@ mod test {
 
     #[test]
     fn test_call_multiple_time_series_id() {
-        let builder = QueryBuilder::begin().series_id(1).series_id(2).series_id(3);
+        let builder = QueryBuilder::begin()
+            .series_id(SeriesId::new(1))
+            .series_id(SeriesId::new(2))
+            .series_id(SeriesId::new(3));
         assert_eq!(
             builder.build().unwrap(),
             Query {
-                series_id: Some(vec![1, 2, 3]),
+                series_id: Some(vec![SeriesId::new(1), SeriesId::new(2), SeriesId::new(3)]),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_add_group_subdomains() {
+        let builder = QueryBuilder::begin().group_subdomains(vec!["rust".to_string()]);
+        assert_eq!(
+            builder.build().unwrap(),
+            Query {
+                group_subdomain: Some(vec!["rust".to_string()]),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_call_multiple_time_group_subdomain() {
+        let builder = QueryBuilder::begin()
+            .group_subdomain("rust")
+            .group_subdomain("rust-osaka");
+        assert_eq!(
+            builder.build().unwrap(),
+            Query {
+                group_subdomain: Some(vec!["rust".to_string(), "rust-osaka".to_string()]),
                 ..Default::default()
             }
         );
@@ -601,20 +818,10 @@ mod test {
     #[test]
     fn test_validation_count_range() {
         let builder = QueryBuilder::begin().count(0);
-        assert!(matches!(
-            builder.build(),
-            Err(ConnpassCliError::Validation(ValidationError::OutOfRange {
-                msg: _
-            }))
-        ));
+        assert_eq!(invalid_fields(builder), vec!["count"]);
 
         let builder = QueryBuilder::begin().count(101);
-        assert!(matches!(
-            builder.build(),
-            Err(ConnpassCliError::Validation(ValidationError::OutOfRange {
-                msg: _
-            }))
-        ));
+        assert_eq!(invalid_fields(builder), vec!["count"]);
     }
 
     #[test]
@@ -632,11 +839,32 @@ mod test {
     #[test]
     fn test_validation_format() {
         let builder = QueryBuilder::begin().format("yaml");
-        assert!(matches!(
-            builder.build(),
-            Err(ConnpassCliError::Validation(
-                ValidationError::InvalidToken { msg: _ }
-            ))
-        ));
+        assert_eq!(invalid_fields(builder), vec!["format"]);
+    }
+
+    #[test]
+    fn test_validation_collects_every_error() {
+        let builder = QueryBuilder::begin()
+            .event_id(EventId::new(0))
+            .keyword("")
+            .ym(202113)
+            .ymd(20210230)
+            .series_id(SeriesId::new(0))
+            .start(0)
+            .count(0)
+            .format("yaml");
+        assert_eq!(
+            invalid_fields(builder),
+            vec![
+                "event_id",
+                "keyword",
+                "ym",
+                "ymd",
+                "series_id",
+                "start",
+                "count",
+                "format"
+            ]
+        );
     }
 }
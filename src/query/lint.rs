@@ -0,0 +1,173 @@
+//! Lints for queries which are valid, but don't work as expected.
+//! Unlike the validation in `QueryBuilder::build`, the problems found here are just warnings,
+//! e.g. for telling users why a search returned nothing.
+
+use std::{collections::HashSet, fmt};
+
+use super::Query;
+
+/// `count` applied by connpass API when it's not set.
+const DEFAULT_COUNT: u8 = 10;
+
+/// A problem found by `Query::lint`.
+#[derive(PartialEq, Clone, Debug)]
+pub enum LintWarning {
+    /// `start` is beyond the number of the results, so nothing is returned.
+    StartBeyondResults { start: u32, results_available: u32 },
+    /// `event_id` is combined with other filters, which narrow the events down further instead of adding more.
+    EventIdWithFilters { fields: Vec<&'static str> },
+    /// The keyword appears more than once in `keyword` and `keyword_or`, which has no effect.
+    DuplicatedKeyword { keyword: String },
+    /// More event ids are set than `count` (10 if not set), so some of the events are not returned.
+    TooManyEventIds { event_ids: usize, count: u8 },
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintWarning::StartBeyondResults {
+                start,
+                results_available,
+            } => write!(
+                f,
+                "`start` is {}, but only {} events are available",
+                start, results_available
+            ),
+            LintWarning::EventIdWithFilters { fields } => write!(
+                f,
+                "`event_id` is combined with {}, which may exclude the events",
+                fields
+                    .iter()
+                    .map(|field| format!("`{}`", field))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            LintWarning::DuplicatedKeyword { keyword } => {
+                write!(f, "\"{}\" is set more than once as a keyword", keyword)
+            }
+            LintWarning::TooManyEventIds { event_ids, count } => write!(
+                f,
+                "{} event ids are set, but at most {} events are returned by `count`",
+                event_ids, count
+            ),
+        }
+    }
+}
+
+impl Query {
+    /// Checks the combinations of the fields which are valid but ineffective or contradictory.
+    pub fn lint(&self) -> Vec<LintWarning> {
+        let mut warnings = Vec::new();
+
+        if !self.event_ids().is_empty() {
+            let filters = [
+                ("keyword", self.keywords().is_empty()),
+                ("keyword_or", self.keywords_or().is_empty()),
+                ("ym", self.yms().is_empty()),
+                ("ymd", self.ymds().is_empty()),
+                ("nickname", self.nicknames().is_empty()),
+                ("owner_nickname", self.owner_nicknames().is_empty()),
+                ("series_id", self.series_ids().is_empty()),
+                ("group_subdomain", self.group_subdomains().is_empty()),
+            ];
+            let fields: Vec<_> = filters
+                .iter()
+                .filter(|(_, is_empty)| !is_empty)
+                .map(|(field, _)| *field)
+                .collect();
+            if !fields.is_empty() {
+                warnings.push(LintWarning::EventIdWithFilters { fields });
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut duplicated = HashSet::new();
+        for keyword in self.keywords().iter().chain(self.keywords_or()) {
+            if !seen.insert(keyword) && duplicated.insert(keyword) {
+                warnings.push(LintWarning::DuplicatedKeyword {
+                    keyword: keyword.clone(),
+                });
+            }
+        }
+
+        let count = self.count().unwrap_or(DEFAULT_COUNT);
+        if self.event_ids().len() > usize::from(count) {
+            warnings.push(LintWarning::TooManyEventIds {
+                event_ids: self.event_ids().len(),
+                count,
+            });
+        }
+
+        warnings
+    }
+
+    /// Runs `lint`, and also checks `start` against `results_available` of a previous response to the query.
+    pub fn lint_with_results_available(&self, results_available: u32) -> Vec<LintWarning> {
+        let mut warnings = self.lint();
+        if let Some(start) = self
+            .start()
+            .filter(|start| *start > 1 && *start > results_available)
+        {
+            warnings.insert(
+                0,
+                LintWarning::StartBeyondResults {
+                    start,
+                    results_available,
+                },
+            );
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{id::EventId, query::builder::QueryBuilder};
+
+    use super::LintWarning;
+
+    #[test]
+    fn test_lint_valid_query() {
+        let query = QueryBuilder::begin()
+            .keyword("Rust")
+            .keyword_or("Tokyo")
+            .start(11)
+            .build()
+            .unwrap();
+        assert!(query.lint().is_empty());
+        assert!(query.lint_with_results_available(20).is_empty());
+    }
+
+    #[test]
+    fn test_lint_problems() {
+        let query = QueryBuilder::begin()
+            .event_ids((1..=11).map(EventId::new).collect())
+            .keyword("Rust")
+            .keyword("rust")
+            .keyword_or("Rust")
+            .keyword_or("Rust")
+            .ym(202110)
+            .start(21)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.lint_with_results_available(20),
+            vec![
+                LintWarning::StartBeyondResults {
+                    start: 21,
+                    results_available: 20
+                },
+                LintWarning::EventIdWithFilters {
+                    fields: vec!["keyword", "keyword_or", "ym"]
+                },
+                LintWarning::DuplicatedKeyword {
+                    keyword: "Rust".to_string()
+                },
+                LintWarning::TooManyEventIds {
+                    event_ids: 11,
+                    count: 10
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Provides `connpass_query!`, which builds a query declaratively.
+
+/// Builds `Query` from fields like `keyword: ["Rust", "Tokyo"]`, and returns the result of `QueryBuilder::build`.
+///
+/// Each field is named after the setter of `QueryBuilder` for a single value, e.g. `keyword` or `ym`,
+/// and takes a value or a list of values in `[...]`.
+/// `order` takes a variant of `OrderOption` like `Newer`, and `event_id` and `series_id` take numbers as well as ids.
+///
+/// Literal values of `count` and `format` are validated at compile time.
+///
+/// # Example:
+/// ```
+/// use connpass_rs::connpass_query;
+///
+/// let query = connpass_query! {
+///     keyword: ["Rust", "Tokyo"],
+///     ym: 202110,
+///     order: Newer,
+///     count: 100,
+/// }
+/// .unwrap();
+/// assert_eq!(query.keywords(), ["Rust", "Tokyo"]);
+/// ```
+///
+/// A literal `count` out of range doesn't compile:
+/// ```compile_fail
+/// let query = connpass_rs::connpass_query! { keyword: "Rust", count: 101 };
+/// ```
+///
+/// Neither does a literal `format` other than "json":
+/// ```compile_fail
+/// let query = connpass_rs::connpass_query! { keyword: "Rust", format: "xml" };
+/// ```
+#[macro_export]
+macro_rules! connpass_query {
+    (@field $builder:expr;) => { $builder };
+    (@field $builder:expr; count: $count:literal $(, $($rest:tt)*)?) => {{
+        const _: () = assert!(
+            $count >= 1 && $count <= 100,
+            "`count` should be greater than or equal to 1 or less than or equals to 100"
+        );
+        $crate::connpass_query!(@field $builder.count($count); $($($rest)*)?)
+    }};
+    (@field $builder:expr; format: $format:literal $(, $($rest:tt)*)?) => {{
+        const _: () = assert!(
+            $crate::query::macros::is_json($format),
+            "`format` can just accept the string \"json\""
+        );
+        $crate::connpass_query!(@field $builder.format($format); $($($rest)*)?)
+    }};
+    (@field $builder:expr; order: $order:ident $(, $($rest:tt)*)?) => {
+        $crate::connpass_query!(
+            @field $builder.order($crate::query::types::OrderOption::$order); $($($rest)*)?
+        )
+    };
+    (@field $builder:expr; event_id: [$($id:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $crate::connpass_query!(
+            @field $builder$(.event_id($crate::id::EventId::from($id)))*; $($($rest)*)?
+        )
+    };
+    (@field $builder:expr; event_id: $id:expr $(, $($rest:tt)*)?) => {
+        $crate::connpass_query!(
+            @field $builder.event_id($crate::id::EventId::from($id)); $($($rest)*)?
+        )
+    };
+    (@field $builder:expr; series_id: [$($id:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $crate::connpass_query!(
+            @field $builder$(.series_id($crate::id::SeriesId::from($id)))*; $($($rest)*)?
+        )
+    };
+    (@field $builder:expr; series_id: $id:expr $(, $($rest:tt)*)?) => {
+        $crate::connpass_query!(
+            @field $builder.series_id($crate::id::SeriesId::from($id)); $($($rest)*)?
+        )
+    };
+    (@field $builder:expr; $field:ident: [$($value:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $crate::connpass_query!(@field $builder$(.$field($value))*; $($($rest)*)?)
+    };
+    (@field $builder:expr; $field:ident: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::connpass_query!(@field $builder.$field($value); $($($rest)*)?)
+    };
+
+    ($($fields:tt)*) => {{
+        let builder = $crate::query::builder::QueryBuilder::begin();
+        $crate::connpass_query!(@field builder; $($fields)*).build()
+    }};
+}
+
+/// Used by `connpass_query!` to check `format` at compile time.
+#[doc(hidden)]
+pub const fn is_json(format: &str) -> bool {
+    let (format, json) = (format.as_bytes(), b"json");
+    if format.len() != json.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < json.len() {
+        if format[i] != json[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        id::{EventId, SeriesId},
+        query::{builder::QueryBuilder, types::OrderOption},
+    };
+
+    #[test]
+    fn test_connpass_query() {
+        let query = connpass_query! {
+            event_id: [1, EventId::new(2)],
+            keyword: ["Rust", "Tokyo"],
+            keyword_or: "Python",
+            ym: 202110,
+            series_id: 4424,
+            order: Newer,
+            count: 100,
+            format: "json",
+        };
+        let expected = QueryBuilder::begin()
+            .event_ids(vec![EventId::new(1), EventId::new(2)])
+            .keywords(vec!["Rust".to_string(), "Tokyo".to_string()])
+            .keyword_or("Python")
+            .ym(202110)
+            .series_id(SeriesId::new(4424))
+            .order(OrderOption::Newer)
+            .count(100)
+            .format("json")
+            .build();
+        assert_eq!(query.unwrap(), expected.unwrap());
+    }
+
+    #[test]
+    fn test_connpass_query_validates_at_runtime() {
+        let count = 0;
+        assert!(connpass_query! { count: count }.is_err());
+        assert!(connpass_query! { ym: 202113 }.is_err());
+        assert!(connpass_query! {}.unwrap().keywords().is_empty());
+    }
+}
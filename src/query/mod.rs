@@ -1,28 +1,54 @@
 //! Provides the utility data for query parameters.
 //! The core data type is `Query`, but it's recommended that build it through `QueryBuilder`.
 
+use serde::{Deserialize, Serialize};
+
 use self::types::OrderOption;
+use crate::id::{EventId, SeriesId};
 
 pub mod builder;
+pub mod lint;
+#[doc(hidden)]
+pub mod macros;
+mod repr;
 pub mod types;
 pub mod validator;
 
 /// A query data to extract the specific data from connpass API.
 /// For more details about the respective fields: https://connpass.com/about/api/
 /// The struct is along with the specification.
-#[derive(PartialEq, Debug)]
+///
+/// `Query` can be converted into a URL query string by `to_string` and parsed back by `parse`,
+/// and can be (de)serialized with serde, e.g. to save searches. Parsed or deserialized queries are validated by `QueryBuilder::build`.
+/// Valid but ineffective combinations of the fields can be checked by `lint`.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(try_from = "repr::RawQuery")]
 pub struct Query {
-    event_id: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event_id: Option<Vec<EventId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     keyword: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     keyword_or: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     ym: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     ymd: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     nickname: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     owner_nickname: Option<Vec<String>>,
-    series_id: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    series_id: Option<Vec<SeriesId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group_subdomain: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     start: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     order: Option<OrderOption>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     count: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     format: Option<String>,
 }
 
@@ -37,6 +63,7 @@ impl Default for Query {
             nickname: None,
             owner_nickname: None,
             series_id: None,
+            group_subdomain: None,
             start: None,
             order: None,
             count: None,
@@ -46,6 +73,75 @@ impl Default for Query {
 }
 
 impl Query {
+    pub fn event_ids(&self) -> &[EventId] {
+        self.event_id.as_deref().unwrap_or_default()
+    }
+
+    pub fn keywords(&self) -> &[String] {
+        self.keyword.as_deref().unwrap_or_default()
+    }
+
+    pub fn keywords_or(&self) -> &[String] {
+        self.keyword_or.as_deref().unwrap_or_default()
+    }
+
+    pub fn yms(&self) -> &[u32] {
+        self.ym.as_deref().unwrap_or_default()
+    }
+
+    pub fn ymds(&self) -> &[u32] {
+        self.ymd.as_deref().unwrap_or_default()
+    }
+
+    pub fn nicknames(&self) -> &[String] {
+        self.nickname.as_deref().unwrap_or_default()
+    }
+
+    pub fn owner_nicknames(&self) -> &[String] {
+        self.owner_nickname.as_deref().unwrap_or_default()
+    }
+
+    pub fn series_ids(&self) -> &[SeriesId] {
+        self.series_id.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the group subdomains not resolved into series ids yet.
+    pub fn group_subdomains(&self) -> &[String] {
+        self.group_subdomain.as_deref().unwrap_or_default()
+    }
+
+    pub fn start(&self) -> Option<u32> {
+        self.start
+    }
+
+    pub fn order(&self) -> Option<OrderOption> {
+        self.order
+    }
+
+    pub fn count(&self) -> Option<u8> {
+        self.count
+    }
+
+    pub fn format(&self) -> Option<&str> {
+        self.format.as_deref()
+    }
+
+    /// Returns the query whose `start` is replaced. This is used for fetching the next page.
+    pub(crate) fn with_start(mut self, start: u32) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Takes the group subdomains out of the query to resolve them into series ids.
+    pub(crate) fn take_group_subdomains(&mut self) -> Vec<String> {
+        self.group_subdomain.take().unwrap_or_default()
+    }
+
+    pub(crate) fn push_series_id(&mut self, series_id: SeriesId) {
+        self.series_id.get_or_insert_with(Vec::new).push(series_id);
+    }
+
+    /// Group subdomains are not a parameter of connpass API, so they have to be resolved before calling this.
     pub(crate) fn make_reqwest_query(self) -> Vec<(String, String)> {
         let mut queries = Vec::new();
 
@@ -94,7 +190,10 @@ fn make_elem<T: ToString>(key: &str, value: T) -> (String, String) {
 
 #[cfg(test)]
 mod test {
-    use crate::query::{assemble_query_param, assemble_single_query_param, make_elem};
+    use crate::{
+        id::EventId,
+        query::{assemble_query_param, assemble_single_query_param, make_elem},
+    };
 
     use super::{builder::QueryBuilder, types::OrderOption};
 
@@ -130,12 +229,13 @@ mod test {
     #[test]
     fn test_make_reqwest_query_partially() {
         let builder = QueryBuilder::begin()
-            .event_ids(vec![1, 2, 3])
+            .event_ids(vec![EventId::new(1), EventId::new(2), EventId::new(3)])
             .keyword("Rust".to_string())
             .start(1)
             .order(OrderOption::Newer)
             .count(10)
-            .format("json".to_string());
+            .format("json".to_string())
+            .group_subdomain("rust");
         let query = builder.build().unwrap();
         let reqwest_query = query.make_reqwest_query();
         assert_eq!(
@@ -0,0 +1,261 @@
+//! Conversions of `Query` from and into URL query strings and serde formats.
+//! Both are validated by `QueryBuilder::build`, so an invalid `Query` can't be made through them.
+//!
+//! `group_subdomain` is not a parameter of connpass API, but it's kept in the query string
+//! as `group_subdomain=...` so that the conversions don't lose it.
+
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+use reqwest::Url;
+use serde::Deserialize;
+
+use super::{builder::QueryBuilder, types::OrderOption, Query};
+use crate::{
+    client::BASE_URL,
+    errors::{ConnpassCliError, ConnpassResult, ValidationError},
+    id::{EventId, SeriesId},
+};
+
+/// The fields of `Query` before validation.
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(super) struct RawQuery {
+    event_id: Option<Vec<EventId>>,
+    keyword: Option<Vec<String>>,
+    keyword_or: Option<Vec<String>>,
+    ym: Option<Vec<u32>>,
+    ymd: Option<Vec<u32>>,
+    nickname: Option<Vec<String>>,
+    owner_nickname: Option<Vec<String>>,
+    series_id: Option<Vec<SeriesId>>,
+    group_subdomain: Option<Vec<String>>,
+    start: Option<u32>,
+    order: Option<OrderOption>,
+    count: Option<u8>,
+    format: Option<String>,
+}
+
+impl TryFrom<RawQuery> for Query {
+    type Error = ConnpassCliError;
+
+    fn try_from(raw: RawQuery) -> ConnpassResult<Self> {
+        let mut builder = QueryBuilder::begin();
+        // Calls the setters only for the present fields, so that absent ones stay `None`.
+        macro_rules! set {
+            ($($field:ident => $setter:ident),* $(,)?) => {
+                $(
+                    if let Some(value) = raw.$field {
+                        builder = builder.$setter(value);
+                    }
+                )*
+            };
+        }
+        set!(
+            event_id => event_ids,
+            keyword => keywords,
+            keyword_or => keywords_or,
+            ym => yms,
+            ymd => ymds,
+            nickname => nicknames,
+            owner_nickname => owner_nicknames,
+            series_id => series_ids,
+            group_subdomain => group_subdomains,
+            start => start,
+            order => order,
+            count => count,
+            format => format,
+        );
+        builder.build()
+    }
+}
+
+impl Query {
+    /// Returns the URL of connpass API with this query.
+    pub fn to_url(&self) -> String {
+        let query = self.to_string();
+        if query.is_empty() {
+            BASE_URL.to_string()
+        } else {
+            format!("{}?{}", BASE_URL, query)
+        }
+    }
+}
+
+/// Formats the query as a URL query string like `keyword=Rust&count=100`.
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut query = self.clone();
+        let group_subdomains = query.take_group_subdomains();
+        let mut url = Url::parse(BASE_URL).map_err(|_| fmt::Error)?;
+        url.query_pairs_mut()
+            .extend_pairs(query.make_reqwest_query())
+            .extend_pairs(
+                group_subdomains
+                    .iter()
+                    .map(|subdomain| ("group_subdomain", subdomain)),
+            );
+        f.write_str(url.query().unwrap_or_default())
+    }
+}
+
+/// Parses a URL query string like `keyword=Rust&count=100` (with or without the leading "?"),
+/// or a URL of connpass API like `https://connpass.com/api/v1/event/?keyword=Rust`.
+impl FromStr for Query {
+    type Err = ConnpassCliError;
+
+    fn from_str(s: &str) -> ConnpassResult<Self> {
+        let url = if s.starts_with("http://") || s.starts_with("https://") {
+            let url = Url::parse(s).map_err(|_| invalid(format!("Invalid URL: {}", s)))?;
+            let base = Url::parse(BASE_URL).map_err(|_| invalid(BASE_URL.to_string()))?;
+            let is_api = url.host_str() == base.host_str()
+                && url.path().trim_end_matches('/') == base.path().trim_end_matches('/');
+            if !is_api {
+                return Err(invalid(format!("Not a URL of connpass API: {}", s)));
+            }
+            url
+        } else {
+            let query = s.trim_start_matches('?');
+            Url::parse(&format!("{}?{}", BASE_URL, query))
+                .map_err(|_| invalid(format!("Invalid query string: {}", s)))?
+        };
+
+        let mut raw = RawQuery::default();
+        for (key, value) in url.query_pairs() {
+            let value = value.into_owned();
+            match key.as_ref() {
+                "event_id" => push(&mut raw.event_id, value.parse()?),
+                "keyword" => push(&mut raw.keyword, value),
+                "keyword_or" => push(&mut raw.keyword_or, value),
+                "ym" => push(&mut raw.ym, parse_number(&key, &value)?),
+                "ymd" => push(&mut raw.ymd, parse_number(&key, &value)?),
+                "nickname" => push(&mut raw.nickname, value),
+                "owner_nickname" => push(&mut raw.owner_nickname, value),
+                "series_id" => push(&mut raw.series_id, value.parse()?),
+                "group_subdomain" => push(&mut raw.group_subdomain, value),
+                "start" => raw.start = Some(parse_number(&key, &value)?),
+                "order" => raw.order = Some(OrderOption::new(parse_number(&key, &value)?)?),
+                "count" => raw.count = Some(parse_number(&key, &value)?),
+                "format" => raw.format = Some(value),
+                _ => return Err(invalid(format!("Unknown query parameter: {}", key))),
+            }
+        }
+        Query::try_from(raw)
+    }
+}
+
+fn push<T>(values: &mut Option<Vec<T>>, value: T) {
+    values.get_or_insert_with(Vec::new).push(value);
+}
+
+fn parse_number<T: FromStr>(key: &str, value: &str) -> ConnpassResult<T> {
+    value
+        .parse()
+        .map_err(|_| invalid(format!("`{}` should be a number: {}", key, value)))
+}
+
+fn invalid(msg: String) -> ConnpassCliError {
+    ConnpassCliError::Validation(ValidationError::InvalidToken { msg })
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::{
+        errors::{ConnpassCliError, ValidationError},
+        id::EventId,
+        query::{builder::QueryBuilder, types::OrderOption, Query},
+    };
+
+    fn query() -> Query {
+        QueryBuilder::begin()
+            .event_ids(vec![EventId::new(1), EventId::new(2)])
+            .keyword("Rust")
+            .keyword("東京")
+            .keyword_or("a&b")
+            .group_subdomain("rust")
+            .order(OrderOption::Newer)
+            .count(100)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            query().to_string(),
+            "event_id=1&event_id=2&keyword=Rust&keyword=%E6%9D%B1%E4%BA%AC&keyword_or=a%26b\
+             &order=3&count=100&group_subdomain=rust"
+        );
+        assert_eq!(Query::default().to_string(), "");
+        assert_eq!(
+            QueryBuilder::begin()
+                .keyword("Rust")
+                .build()
+                .unwrap()
+                .to_url(),
+            "https://connpass.com/api/v1/event/?keyword=Rust"
+        );
+    }
+
+    #[test]
+    fn test_round_trip_query_string() {
+        let query = query();
+        assert_eq!(query.to_string().parse::<Query>().unwrap(), query);
+        assert_eq!(query.to_url().parse::<Query>().unwrap(), query);
+        assert_eq!("".parse::<Query>().unwrap(), Query::default());
+    }
+
+    #[test]
+    fn test_parse_query_string() {
+        let query: Query = "?keyword=Rust+Tokyo&start=11".parse().unwrap();
+        assert_eq!(query.keywords(), ["Rust Tokyo"]);
+        assert_eq!(query.start(), Some(11));
+        assert_eq!(query.count(), None);
+    }
+
+    #[test]
+    fn test_parse_invalid_query_string() {
+        for s in [
+            "count=0",
+            "count=1000",
+            "format=xml",
+            "order=4",
+            "event_id=abc",
+            "unknown=1",
+            "https://example.com/api/v1/event/?keyword=Rust",
+            "https://connpass.com/event/?keyword=Rust",
+        ] {
+            assert!(
+                matches!(s.parse::<Query>(), Err(ConnpassCliError::Validation(_))),
+                "{}",
+                s
+            );
+        }
+        assert!(matches!(
+            "count=0".parse::<Query>(),
+            Err(ConnpassCliError::Validation(ValidationError::Fields(errors)))
+                if errors.errors()[0].field() == "count"
+        ));
+    }
+
+    #[test]
+    fn test_serde() {
+        let value = serde_json::to_value(query()).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "event_id": [1, 2],
+                "keyword": ["Rust", "東京"],
+                "keyword_or": ["a&b"],
+                "group_subdomain": ["rust"],
+                "order": "newer",
+                "count": 100,
+            })
+        );
+        assert_eq!(serde_json::from_value::<Query>(value).unwrap(), query());
+
+        assert!(serde_json::from_value::<Query>(json!({ "count": 0 })).is_err());
+        assert!(serde_json::from_value::<Query>(json!({ "unknown": 1 })).is_err());
+    }
+}
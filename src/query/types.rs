@@ -1,5 +1,7 @@
 use std::convert::TryFrom;
 
+use serde::{Deserialize, Serialize};
+
 use crate::errors::{ConnpassCliError, ConnpassResult, ValidationError};
 
 use super::validator::Validator;
@@ -34,9 +36,58 @@ impl Validator for FormatJson {
     }
 }
 
+/// A data type that represents a `ym` value like `202110`.
+pub(crate) struct YearMonth(pub u32);
+
+impl Validator for YearMonth {
+    fn validate(self) -> ConnpassResult<Self> {
+        let (year, month) = (self.0 / 100, self.0 % 100);
+        if (1000..=9999).contains(&year) && (1..=12).contains(&month) {
+            Ok(self)
+        } else {
+            Err(ConnpassCliError::Validation(
+                ValidationError::InvalidToken {
+                    msg: format!("`ym` should be a year and month like 202110: {}", self.0),
+                },
+            ))
+        }
+    }
+}
+
+/// A data type that represents a `ymd` value like `20211030`.
+pub(crate) struct YearMonthDay(pub u32);
+
+impl Validator for YearMonthDay {
+    fn validate(self) -> ConnpassResult<Self> {
+        let (year, month, day) = (self.0 / 10000, self.0 / 100 % 100, self.0 % 100);
+        if (1000..=9999).contains(&year)
+            && (1..=12).contains(&month)
+            && (1..=days_in_month(year, month)).contains(&day)
+        {
+            Ok(self)
+        } else {
+            Err(ConnpassCliError::Validation(
+                ValidationError::InvalidToken {
+                    msg: format!("`ymd` should be a date like 20211030: {}", self.0),
+                },
+            ))
+        }
+    }
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
 /// The order of search result.
 /// See more details in https://connpass.com/about/api/.
-#[derive(PartialEq, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
 pub enum OrderOption {
     /// 「更新日時順」
     LastModifiedDate = 1,
@@ -90,7 +141,7 @@ mod test {
         query::validator::Validator,
     };
 
-    use super::{FetchCountRange, FormatJson};
+    use super::{FetchCountRange, FormatJson, YearMonth, YearMonthDay};
 
     #[test]
     fn test_validate_fetch_count_range() {
@@ -136,4 +187,21 @@ mod test {
             ))
         ));
     }
+
+    #[test]
+    fn test_validate_year_month() {
+        assert!(YearMonth(202110).validate().is_ok());
+        assert!(YearMonth(202113).validate().is_err());
+        assert!(YearMonth(202100).validate().is_err());
+        assert!(YearMonth(2021).validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_year_month_day() {
+        assert!(YearMonthDay(20211030).validate().is_ok());
+        assert!(YearMonthDay(20240229).validate().is_ok());
+        assert!(YearMonthDay(20210229).validate().is_err());
+        assert!(YearMonthDay(20211131).validate().is_err());
+        assert!(YearMonthDay(202110).validate().is_err());
+    }
 }
@@ -1,4 +1,12 @@
-use crate::errors::ConnpassCliError;
+//! Validation of queries.
+//! In addition to the checks in `QueryBuilder::build`, which follow the specification of connpass API,
+//! your own rules can be defined as `Policy` and applied by `QueryBuilder::build_with` or `ConnpassClient::with_policies`.
+
+use std::sync::Arc;
+
+use crate::errors::{ConnpassCliError, FieldError, ValidationError, ValidationErrors};
+
+use super::Query;
 
 pub(crate) trait Validator
 where
@@ -6,3 +14,167 @@ where
 {
     fn validate(self) -> Result<Self, ConnpassCliError>;
 }
+
+/// A rule which queries have to follow, e.g. an organization-specific one.
+///
+/// Policies check built queries, so a `QueryBuilder` can't be checked as it is.
+/// Use `QueryBuilder::build_with` to check it by the policies right after the checks of `build`.
+///
+/// Closures taking `&Query` and returning `Vec<FieldError>` are policies as well.
+pub trait Policy: Send + Sync {
+    /// Checks the query, and returns every problem found. An empty `Vec` means the query follows the rule.
+    fn check(&self, query: &Query) -> Vec<FieldError>;
+}
+
+impl<F> Policy for F
+where
+    F: Fn(&Query) -> Vec<FieldError> + Send + Sync,
+{
+    fn check(&self, query: &Query) -> Vec<FieldError> {
+        self(query)
+    }
+}
+
+/// Requires `count` to be set to the value, e.g. `RequireCount(100)` for batch jobs fetching as many events as possible per request.
+#[derive(Clone, Copy, Debug)]
+pub struct RequireCount(pub u8);
+
+impl Policy for RequireCount {
+    fn check(&self, query: &Query) -> Vec<FieldError> {
+        if query.count() == Some(self.0) {
+            return Vec::new();
+        }
+        vec![FieldError::new(
+            "count",
+            ValidationError::InvalidToken {
+                msg: format!("`count` should be set to {}", self.0),
+            },
+        )]
+    }
+}
+
+/// Requires `ym` or `ymd` to be set, so that queries are not unbounded.
+/// Queries by `event_id` are bounded by the ids, so they are allowed as well.
+#[derive(Clone, Copy, Debug)]
+pub struct RequireDateFilter;
+
+impl Policy for RequireDateFilter {
+    fn check(&self, query: &Query) -> Vec<FieldError> {
+        if !query.yms().is_empty() || !query.ymds().is_empty() || !query.event_ids().is_empty() {
+            return Vec::new();
+        }
+        vec![FieldError::new(
+            "ym",
+            ValidationError::InvalidToken {
+                msg: "`ym` or `ymd` should be set".to_string(),
+            },
+        )]
+    }
+}
+
+/// A set of policies, which is a policy checking all of them.
+#[derive(Clone, Default)]
+pub struct Policies {
+    policies: Vec<Arc<dyn Policy>>,
+}
+
+impl Policies {
+    pub fn new() -> Self {
+        Policies::default()
+    }
+
+    /// Adds the policy to the set.
+    pub fn with(mut self, policy: impl Policy + 'static) -> Self {
+        self.policies.push(Arc::new(policy));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    /// Checks the query against every policy, and returns the problems together as `ValidationError::Fields`.
+    pub fn validate(&self, query: &Query) -> Result<(), ConnpassCliError> {
+        let errors = self.check(query);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConnpassCliError::Validation(ValidationError::Fields(
+                ValidationErrors::new(errors),
+            )))
+        }
+    }
+}
+
+impl Policy for Policies {
+    fn check(&self, query: &Query) -> Vec<FieldError> {
+        self.policies
+            .iter()
+            .flat_map(|policy| policy.check(query))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        errors::{ConnpassCliError, FieldError, ValidationError},
+        query::{builder::QueryBuilder, Query},
+    };
+
+    use super::{Policies, Policy, RequireCount, RequireDateFilter};
+
+    fn policies() -> Policies {
+        Policies::new()
+            .with(RequireCount(100))
+            .with(RequireDateFilter)
+            .with(|query: &Query| {
+                query
+                    .keywords()
+                    .iter()
+                    .filter(|keyword| keyword.as_str() == "spam")
+                    .map(|_| {
+                        FieldError::new(
+                            "keyword",
+                            ValidationError::InvalidToken {
+                                msg: "spam is not allowed".to_string(),
+                            },
+                        )
+                    })
+                    .collect()
+            })
+    }
+
+    #[test]
+    fn test_policies() {
+        let query = QueryBuilder::begin()
+            .keyword("spam")
+            .count(10)
+            .build()
+            .unwrap();
+        let fields: Vec<_> = policies().check(&query).iter().map(|e| e.field()).collect();
+        assert_eq!(fields, vec!["count", "ym", "keyword"]);
+
+        let query = QueryBuilder::begin().ym(202110).count(100).build().unwrap();
+        assert!(policies().validate(&query).is_ok());
+    }
+
+    #[test]
+    fn test_build_with_policies() {
+        let result = QueryBuilder::begin()
+            .count(0)
+            .ym(202110)
+            .build_with(&policies());
+        assert!(matches!(
+            result,
+            Err(ConnpassCliError::Validation(ValidationError::Fields(errors)))
+                if errors.errors().len() == 1 && errors.errors()[0].field() == "count"
+        ));
+
+        assert!(QueryBuilder::begin()
+            .count(100)
+            .ym(202110)
+            .build_with(&policies())
+            .is_ok());
+    }
+}
@@ -0,0 +1,160 @@
+//! Resolves group subdomains (e.g. `rust` of `rust.connpass.com`) into series ids.
+//!
+//! connpass API has no endpoint for groups, so the series id is found by sampling events:
+//! events are searched with the subdomain as a keyword, and the series id of an event held by the group is taken.
+//! This means that a group whose events don't mention the subdomain can't be resolved.
+//! In that case, `ResolveError::SubdomainNotFound` is returned, and the mapping can be registered by hand with `SeriesResolver::insert`.
+//!
+//! The resolved mappings are cached, and the cache is shared among the clones of a resolver.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    errors::{ConnpassCliError, ConnpassResult, ResolveError},
+    id::SeriesId,
+    query::{builder::QueryBuilder, types::OrderOption, Query},
+    response::ConnpassResponse,
+    url::ConnpassUrl,
+};
+
+/// How many pages are sampled at most before giving up.
+pub(crate) const MAX_SAMPLED_PAGES: u32 = 3;
+const SAMPLED_COUNT: u8 = 100;
+
+/// A cache of the mappings from group subdomains to series ids.
+#[derive(Clone, Default)]
+pub struct SeriesResolver {
+    cache: Arc<Mutex<HashMap<String, SeriesId>>>,
+}
+
+impl SeriesResolver {
+    pub fn new() -> Self {
+        SeriesResolver::default()
+    }
+
+    /// Registers the mapping by hand.
+    pub fn insert(&self, subdomain: impl Into<String>, series_id: SeriesId) {
+        self.lock().insert(subdomain.into(), series_id);
+    }
+
+    /// Returns the cached series id of the subdomain.
+    pub fn cached(&self, subdomain: &str) -> Option<SeriesId> {
+        self.lock().get(subdomain).copied()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, SeriesId>> {
+        // The map is always left consistent, so a poisoned lock can be used as it is.
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Looks for the series id of the subdomain in the sampled response, and caches it if found.
+    pub(crate) fn find_in(&self, subdomain: &str, res: &ConnpassResponse) -> Option<SeriesId> {
+        let series_id = res.events().iter().find_map(|event| {
+            let series = event.series()?;
+            let held_by_group = [series.url(), event.event_url()]
+                .iter()
+                .flatten()
+                .any(|url| belongs_to(url, subdomain));
+            if held_by_group {
+                Some(series.id())
+            } else {
+                None
+            }
+        })?;
+        self.insert(subdomain, series_id);
+        Some(series_id)
+    }
+}
+
+/// Makes the query for sampling the `page`-th page (starting from 0) of the events of the group.
+pub(crate) fn sample_query(subdomain: &str, page: u32) -> ConnpassResult<Query> {
+    QueryBuilder::begin()
+        .keyword(subdomain)
+        .order(OrderOption::Newer)
+        .count(SAMPLED_COUNT)
+        .start(page * u32::from(SAMPLED_COUNT) + 1)
+        .build()
+}
+
+pub(crate) fn unresolved(subdomain: &str) -> ConnpassCliError {
+    ConnpassCliError::Resolve(ResolveError::SubdomainNotFound(subdomain.to_string()))
+}
+
+fn belongs_to(url: &str, subdomain: &str) -> bool {
+    match url.parse() {
+        Ok(ConnpassUrl::Group(group)) => group.subdomain() == subdomain,
+        Ok(ConnpassUrl::Event(event)) => event.subdomain() == Some(subdomain),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::{id::SeriesId, response::ConnpassResponse};
+
+    use super::{sample_query, SeriesResolver};
+
+    fn response() -> ConnpassResponse {
+        serde_json::from_value(json!({
+            "results_returned": 3,
+            "results_available": 250,
+            "results_start": 1,
+            "events": [
+                {
+                    "event_id": 1,
+                    "event_url": "https://rust-osaka.connpass.com/event/1/",
+                    "series": { "id": 100, "url": "https://rust-osaka.connpass.com/" },
+                },
+                {
+                    "event_id": 2,
+                    "event_url": "https://connpass.com/event/2/",
+                },
+                {
+                    "event_id": 3,
+                    "event_url": "https://rust.connpass.com/event/3/",
+                    "series": { "id": 4424, "url": "https://rust.connpass.com/" },
+                },
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_find_in() {
+        let resolver = SeriesResolver::new();
+        assert_eq!(
+            resolver.find_in("rust", &response()),
+            Some(SeriesId::new(4424))
+        );
+        assert_eq!(resolver.cached("rust"), Some(SeriesId::new(4424)));
+
+        assert_eq!(resolver.find_in("rust-tokyo", &response()), None);
+        assert_eq!(resolver.cached("rust-tokyo"), None);
+    }
+
+    #[test]
+    fn test_cache_is_shared_among_clones() {
+        let resolver = SeriesResolver::new();
+        resolver.clone().insert("rust", SeriesId::new(4424));
+        assert_eq!(resolver.cached("rust"), Some(SeriesId::new(4424)));
+    }
+
+    #[test]
+    fn test_sample_query() {
+        let query = sample_query("rust", 1).unwrap();
+        assert_eq!(
+            query.make_reqwest_query(),
+            vec![
+                ("keyword".to_string(), "rust".to_string()),
+                ("start".to_string(), "101".to_string()),
+                ("order".to_string(), "3".to_string()),
+                ("count".to_string(), "100".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! The borrowed variant of the response model, which avoids allocating a `String` for every field.
+//!
+//! Strings are borrowed from the input buffer as long as they contain no escape sequences
+//! (e.g. `\n` or `\u3042`), otherwise they are unescaped into owned strings.
+//! The buffer can be fetched by `ConnpassClient::send_request_bytes`.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::{check_event_types, EventType};
+use crate::errors::{ConnpassCliError, ConnpassResult, HttpResponseError};
+use crate::id::{EventId, SeriesId, UserId};
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct ConnpassResponse<'a> {
+    results_returned: u32,
+    results_available: u32,
+    results_start: u32,
+    #[serde(borrow, deserialize_with = "strict_events")]
+    events: Vec<Event<'a>>,
+}
+
+impl<'a> ConnpassResponse<'a> {
+    /// Decodes the response from the JSON in the buffer, borrowing strings from it.
+    pub fn from_slice(bytes: &'a [u8]) -> ConnpassResult<Self> {
+        serde_json::from_slice(bytes).map_err(|err| {
+            ConnpassCliError::HttpResponse(HttpResponseError::JsonDecode(format!("{}", err)))
+        })
+    }
+
+    pub fn results_returned(&self) -> u32 {
+        self.results_returned
+    }
+
+    pub fn results_available(&self) -> u32 {
+        self.results_available
+    }
+
+    pub fn results_start(&self) -> u32 {
+        self.results_start
+    }
+
+    pub fn events(&self) -> &[Event<'a>] {
+        &self.events
+    }
+
+    /// Consumes the response and returns the events in it.
+    pub fn into_events(self) -> Vec<Event<'a>> {
+        self.events
+    }
+
+    /// Converts into the owned response, copying the borrowed strings.
+    pub fn into_owned(self) -> super::ConnpassResponse {
+        super::ConnpassResponse {
+            results_returned: self.results_returned,
+            results_available: self.results_available,
+            results_start: self.results_start,
+            events: self.events.into_iter().map(Event::into_owned).collect(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// Decodes the events, rejecting the values of `event_type` unknown to this crate like the owned model.
+fn strict_events<'de: 'a, 'a, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Event<'a>>, D::Error> {
+    let events = Vec::<Event<'a>>::deserialize(deserializer)?;
+    check_event_types(events.iter().map(|e| e.event_type.as_ref()))?;
+    Ok(events)
+}
+
+/// Generates getters of `Option<Cow<str>>` fields returning `Option<&str>`.
+macro_rules! str_getters {
+    ($($field:ident),* $(,)?) => {
+        $(
+            pub fn $field(&self) -> Option<&str> {
+                self.$field.as_deref()
+            }
+        )*
+    };
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Event<'a> {
+    event_id: EventId,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    title: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    catch: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    description: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    event_url: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    hash_tag: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    started_at: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    ended_at: Option<Cow<'a, str>>,
+    limit: Option<u32>,
+    event_type: Option<EventType>,
+    #[serde(borrow)]
+    series: Option<Series<'a>>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    address: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    place: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    lat: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    lon: Option<Cow<'a, str>>,
+    owner_id: Option<UserId>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    owner_nickname: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    owner_display_name: Option<Cow<'a, str>>,
+    accepted: Option<u32>,
+    waiting: Option<u32>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    updated_at: Option<Cow<'a, str>>,
+}
+
+impl<'a> Event<'a> {
+    pub fn id(&self) -> EventId {
+        self.event_id
+    }
+
+    str_getters!(
+        title,
+        catch,
+        description,
+        event_url,
+        hash_tag,
+        started_at,
+        ended_at,
+        address,
+        place,
+        lat,
+        lon,
+        owner_nickname,
+        owner_display_name,
+        updated_at,
+    );
+
+    pub fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    pub fn event_type(&self) -> Option<&EventType> {
+        self.event_type.as_ref()
+    }
+
+    pub fn series(&self) -> Option<&Series<'a>> {
+        self.series.as_ref()
+    }
+
+    pub fn owner_id(&self) -> Option<UserId> {
+        self.owner_id
+    }
+
+    pub fn accepted(&self) -> Option<u32> {
+        self.accepted
+    }
+
+    pub fn waiting(&self) -> Option<u32> {
+        self.waiting
+    }
+
+    /// Converts into the owned event, copying the borrowed strings.
+    pub fn into_owned(self) -> super::Event {
+        super::Event {
+            event_id: self.event_id,
+            title: self.title.map(Cow::into_owned),
+            catch: self.catch.map(Cow::into_owned),
+            description: self.description.map(Cow::into_owned),
+            event_url: self.event_url.map(Cow::into_owned),
+            hash_tag: self.hash_tag.map(Cow::into_owned),
+            started_at: self.started_at.map(Cow::into_owned),
+            ended_at: self.ended_at.map(Cow::into_owned),
+            limit: self.limit,
+            event_type: self.event_type,
+            series: self.series.map(Series::into_owned),
+            address: self.address.map(Cow::into_owned),
+            place: self.place.map(Cow::into_owned),
+            lat: self.lat.map(Cow::into_owned),
+            lon: self.lon.map(Cow::into_owned),
+            owner_id: self.owner_id,
+            owner_nickname: self.owner_nickname.map(Cow::into_owned),
+            owner_display_name: self.owner_display_name.map(Cow::into_owned),
+            accepted: self.accepted,
+            waiting: self.waiting,
+            updated_at: self.updated_at.map(Cow::into_owned),
+            extra: Default::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Series<'a> {
+    id: SeriesId,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    title: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed_str")]
+    url: Option<Cow<'a, str>>,
+}
+
+impl<'a> Series<'a> {
+    pub fn id(&self) -> SeriesId {
+        self.id
+    }
+
+    str_getters!(title, url);
+
+    /// Converts into the owned series, copying the borrowed strings.
+    pub fn into_owned(self) -> super::Series {
+        super::Series {
+            id: self.id,
+            title: self.title.map(Cow::into_owned),
+            url: self.url.map(Cow::into_owned),
+        }
+    }
+}
+
+/// `Option<Cow<str>>` is always deserialized into `Cow::Owned` even with `#[serde(borrow)]`,
+/// so the inner `Cow` is wrapped to be borrowed.
+fn borrowed_str<'de: 'a, 'a, D>(deserializer: D) -> Result<Option<Cow<'a, str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    Option::<Borrowed<'a>>::deserialize(deserializer).map(|s| s.map(|s| s.0))
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+
+    use super::ConnpassResponse;
+
+    const JSON: &str = r#"{
+        "results_returned": 1,
+        "results_available": 1,
+        "results_start": 1,
+        "events": [
+            {
+                "event_id": 228732,
+                "title": "Rust.Tokyo 2021",
+                "description": "<p>Rust\n2021</p>",
+                "event_url": "https://rust.connpass.com/event/228732/",
+                "limit": 100,
+                "event_type": "participation",
+                "series": { "id": 4424, "title": "Rust", "url": "https://rust.connpass.com/" },
+                "place": null,
+                "accepted": 30
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_from_slice_borrows_strings() {
+        let res = ConnpassResponse::from_slice(JSON.as_bytes()).unwrap();
+        let event = &res.events()[0];
+        assert!(matches!(
+            event.title,
+            Some(Cow::Borrowed("Rust.Tokyo 2021"))
+        ));
+        assert!(matches!(
+            event.series().unwrap().url,
+            Some(Cow::Borrowed("https://rust.connpass.com/"))
+        ));
+        // Strings with escape sequences can't be borrowed.
+        assert!(matches!(event.description, Some(Cow::Owned(_))));
+        assert_eq!(event.description(), Some("<p>Rust\n2021</p>"));
+        assert_eq!(event.place(), None);
+        assert_eq!(event.catch(), None);
+    }
+
+    #[test]
+    fn test_into_owned() {
+        let borrowed = ConnpassResponse::from_slice(JSON.as_bytes()).unwrap();
+        let owned: crate::response::ConnpassResponse = serde_json::from_str(JSON).unwrap();
+        assert_eq!(borrowed.into_owned(), owned);
+    }
+
+    #[test]
+    fn test_from_slice_invalid_json() {
+        assert!(ConnpassResponse::from_slice(b"{").is_err());
+    }
+
+    #[test]
+    fn test_from_slice_rejects_unknown_event_type() {
+        let json = JSON.replace("\"participation\"", "\"hybrid\"");
+        assert!(ConnpassResponse::from_slice(json.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! Computes field-level differences between two snapshots of the same `Event`.
+//! The differences are serializable, so they can be stored and replayed later by `apply`.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Event, EventField, EventType, Series};
+use crate::{
+    errors::{ConnpassCliError, ConnpassResult, ValidationError},
+    id::{EventId, UserId},
+};
+
+/// The changes of the fields of an event, which are replayed by `apply` on the event with the same id.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct EventDiff {
+    event_id: EventId,
+    changes: Vec<FieldChange>,
+}
+
+impl EventDiff {
+    pub fn id(&self) -> EventId {
+        self.event_id
+    }
+
+    /// Returns the changes in the order of the fields declared in `Event`.
+    pub fn changes(&self) -> &[FieldChange] {
+        &self.changes
+    }
+
+    pub fn into_changes(self) -> Vec<FieldChange> {
+        self.changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Returns the changes from `old` to `new`, which must have the same `event_id`.
+pub fn diff(old: &Event, new: &Event) -> ConnpassResult<EventDiff> {
+    check_id(old.event_id, new.event_id)?;
+    Ok(EventDiff {
+        event_id: old.event_id,
+        changes: field_changes(old, new),
+    })
+}
+
+/// Replays the changes on `event` by overwriting each field with the value after the change.
+/// `event` must have the same `event_id` as the diff, and is left as it is otherwise.
+pub fn apply(event: &mut Event, diff: &EventDiff) -> ConnpassResult<()> {
+    check_id(diff.event_id, event.event_id)?;
+    apply_changes(event, &diff.changes);
+    Ok(())
+}
+
+fn check_id(expected: EventId, found: EventId) -> ConnpassResult<()> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(ConnpassCliError::Validation(
+            ValidationError::EventIdMismatch { expected, found },
+        ))
+    }
+}
+
+macro_rules! field_changes {
+    ($($variant:ident($field:ident: $ty:ty)),* $(,)?) => {
+        /// A change of a single field of `Event`, holding the values before and after the change.
+        #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+        #[serde(tag = "field", rename_all = "snake_case")]
+        pub enum FieldChange {
+            $($variant { old: $ty, new: $ty },)*
+        }
+
+        impl FieldChange {
+            /// Returns which field has changed.
+            pub fn field(&self) -> EventField {
+                match self {
+                    $(FieldChange::$variant { .. } => EventField::$variant,)*
+                }
+            }
+        }
+
+        fn field_changes(old: &Event, new: &Event) -> Vec<FieldChange> {
+            let mut changes = Vec::new();
+            $(
+                if old.$field != new.$field {
+                    changes.push(FieldChange::$variant {
+                        old: old.$field.clone(),
+                        new: new.$field.clone(),
+                    });
+                }
+            )*
+            changes
+        }
+
+        fn apply_changes(event: &mut Event, changes: &[FieldChange]) {
+            for change in changes {
+                match change {
+                    $(FieldChange::$variant { new, .. } => event.$field = new.clone(),)*
+                }
+            }
+        }
+    };
+}
+
+field_changes! {
+    Title(title: Option<String>),
+    Catch(catch: Option<String>),
+    Description(description: Option<String>),
+    EventUrl(event_url: Option<String>),
+    HashTag(hash_tag: Option<String>),
+    StartedAt(started_at: Option<String>),
+    EndedAt(ended_at: Option<String>),
+    Limit(limit: Option<u32>),
+    EventType(event_type: Option<EventType>),
+    Series(series: Option<Series>),
+    Address(address: Option<String>),
+    Place(place: Option<String>),
+    Lat(lat: Option<String>),
+    Lon(lon: Option<String>),
+    OwnerId(owner_id: Option<UserId>),
+    OwnerNickname(owner_nickname: Option<String>),
+    OwnerDisplayName(owner_display_name: Option<String>),
+    Accepted(accepted: Option<u32>),
+    Waiting(waiting: Option<u32>),
+    UpdatedAt(updated_at: Option<String>),
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::{
+        errors::{ConnpassCliError, ValidationError},
+        id::EventId,
+        response::{Event, EventField},
+    };
+
+    use super::{apply, diff, EventDiff, FieldChange};
+
+    fn old_event() -> Event {
+        serde_json::from_value(json!({
+            "event_id": 228732,
+            "title": "Rust.Tokyo 2021",
+            "place": "オンライン",
+            "limit": 100,
+            "accepted": 80,
+            "updated_at": "2021-10-01T10:00:00+09:00",
+        }))
+        .unwrap()
+    }
+
+    fn new_event() -> Event {
+        serde_json::from_value(json!({
+            "event_id": 228732,
+            "title": "Rust.Tokyo 2021",
+            "place": "渋谷",
+            "limit": 120,
+            "accepted": 80,
+            "updated_at": "2021-10-02T10:00:00+09:00",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_diff() {
+        assert!(diff(&old_event(), &old_event()).unwrap().is_empty());
+        let changes = diff(&old_event(), &new_event()).unwrap();
+        assert_eq!(changes.id(), EventId::new(228732));
+        assert_eq!(
+            changes.changes(),
+            [
+                FieldChange::Limit {
+                    old: Some(100),
+                    new: Some(120),
+                },
+                FieldChange::Place {
+                    old: Some("オンライン".to_string()),
+                    new: Some("渋谷".to_string()),
+                },
+                FieldChange::UpdatedAt {
+                    old: Some("2021-10-01T10:00:00+09:00".to_string()),
+                    new: Some("2021-10-02T10:00:00+09:00".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_field() {
+        let fields: Vec<EventField> = diff(&old_event(), &new_event())
+            .unwrap()
+            .changes()
+            .iter()
+            .map(FieldChange::field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec![EventField::Limit, EventField::Place, EventField::UpdatedAt]
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let changes = diff(&old_event(), &new_event()).unwrap();
+        let mut event = old_event();
+        apply(&mut event, &changes).unwrap();
+        assert_eq!(event, new_event());
+    }
+
+    #[test]
+    fn test_event_id_mismatch() {
+        let mut other = new_event();
+        other.event_id = EventId::new(1);
+        assert!(matches!(
+            diff(&old_event(), &other),
+            Err(ConnpassCliError::Validation(
+                ValidationError::EventIdMismatch { .. }
+            ))
+        ));
+
+        let changes = diff(&old_event(), &new_event()).unwrap();
+        let mut event = other.clone();
+        assert!(apply(&mut event, &changes).is_err());
+        assert_eq!(event, other);
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let changes = diff(&old_event(), &new_event()).unwrap();
+        let value = serde_json::to_value(&changes).unwrap();
+        assert_eq!(value["event_id"], json!(228732));
+        assert_eq!(
+            value["changes"][0],
+            json!({ "field": "limit", "old": 100, "new": 120 })
+        );
+
+        let restored: EventDiff = serde_json::from_value(value).unwrap();
+        assert_eq!(restored, changes);
+    }
+}
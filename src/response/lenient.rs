@@ -0,0 +1,275 @@
+//! The lenient mode of decoding responses, which tolerates changes of the API schema.
+//! In this mode:
+//! 1. unknown values of `event_type` are decoded into `EventType::Unknown`.
+//! 2. a field of an event with an unexpected type (e.g. a number for `lat`) is decoded into `None` with a warning.
+//! 3. an event without a valid `event_id` is skipped with a warning.
+//! 4. fields unknown to this crate are kept in `Event::extra`, which is always empty in the strict mode.
+//!
+//! The warnings can be seen by `ConnpassResponse::warnings`.
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use super::{ConnpassResponse, Event, EventType};
+use crate::errors::{ConnpassCliError, ConnpassResult, HttpResponseError};
+
+/// A problem tolerated in the lenient mode.
+#[derive(PartialEq, Clone, Debug)]
+pub struct DecodeWarning {
+    path: String,
+    message: String,
+}
+
+impl DecodeWarning {
+    /// Returns where the problem is, e.g. `events[3].lat`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+pub(super) fn decode(bytes: &[u8]) -> ConnpassResult<ConnpassResponse> {
+    let value: Value =
+        serde_json::from_slice(bytes).map_err(|err| decode_error(err.to_string()))?;
+    let mut fields = match value {
+        Value::Object(map) => map,
+        _ => return Err(decode_error("The response is not an object".to_string())),
+    };
+
+    let mut warnings = Vec::new();
+    let events = match fields.remove("events") {
+        Some(Value::Array(events)) => events
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, event)| decode_event(event, format!("events[{}]", i), &mut warnings))
+            .collect(),
+        _ => return Err(decode_error("`events` is not an array".to_string())),
+    };
+    Ok(ConnpassResponse {
+        results_returned: required(&mut fields, "results_returned")?,
+        results_available: required(&mut fields, "results_available")?,
+        results_start: required(&mut fields, "results_start")?,
+        events,
+        warnings,
+    })
+}
+
+fn decode_event(event: Value, path: String, warnings: &mut Vec<DecodeWarning>) -> Option<Event> {
+    let map = match event {
+        Value::Object(map) => map,
+        _ => {
+            warnings.push(DecodeWarning {
+                path,
+                message: "The event is not an object, so it is skipped".to_string(),
+            });
+            return None;
+        }
+    };
+    let mut fields = Fields {
+        map,
+        path,
+        warnings,
+    };
+    let event_id = match fields.optional("event_id") {
+        Some(event_id) => event_id,
+        None => {
+            fields.warnings.push(DecodeWarning {
+                path: fields.path,
+                message: "The event has no valid `event_id`, so it is skipped".to_string(),
+            });
+            return None;
+        }
+    };
+
+    Some(Event {
+        event_id,
+        title: fields.optional("title"),
+        catch: fields.optional("catch"),
+        description: fields.optional("description"),
+        event_url: fields.optional("event_url"),
+        hash_tag: fields.optional("hash_tag"),
+        started_at: fields.optional("started_at"),
+        ended_at: fields.optional("ended_at"),
+        limit: fields.optional("limit"),
+        event_type: fields.optional("event_type").map(EventType::from_lenient),
+        series: fields.optional("series"),
+        address: fields.optional("address"),
+        place: fields.optional("place"),
+        lat: fields.optional("lat"),
+        lon: fields.optional("lon"),
+        owner_id: fields.optional("owner_id"),
+        owner_nickname: fields.optional("owner_nickname"),
+        owner_display_name: fields.optional("owner_display_name"),
+        accepted: fields.optional("accepted"),
+        waiting: fields.optional("waiting"),
+        updated_at: fields.optional("updated_at"),
+        extra: fields.map.into_iter().collect(),
+    })
+}
+
+/// The fields of an event not decoded yet.
+struct Fields<'w> {
+    map: Map<String, Value>,
+    path: String,
+    warnings: &'w mut Vec<DecodeWarning>,
+}
+
+impl<'w> Fields<'w> {
+    /// Takes the field out and decodes it. A field with an unexpected type is decoded into `None` with a warning.
+    fn optional<T: DeserializeOwned>(&mut self, key: &str) -> Option<T> {
+        let value = match self.map.remove(key) {
+            None | Some(Value::Null) => return None,
+            Some(value) => value,
+        };
+        match serde_json::from_value(value) {
+            Ok(decoded) => Some(decoded),
+            Err(err) => {
+                self.warnings.push(DecodeWarning {
+                    path: format!("{}.{}", self.path, key),
+                    message: err.to_string(),
+                });
+                None
+            }
+        }
+    }
+}
+
+fn required<T: DeserializeOwned>(fields: &mut Map<String, Value>, key: &str) -> ConnpassResult<T> {
+    let value = fields.remove(key).unwrap_or(Value::Null);
+    serde_json::from_value(value).map_err(|err| decode_error(format!("`{}`: {}", key, err)))
+}
+
+fn decode_error(msg: String) -> ConnpassCliError {
+    ConnpassCliError::HttpResponse(HttpResponseError::JsonDecode(msg))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::response::{
+        diff::{diff, EventDiff},
+        ConnpassResponse, Event, EventType,
+    };
+
+    fn decode(value: serde_json::Value) -> ConnpassResponse {
+        ConnpassResponse::from_slice_lenient(&serde_json::to_vec(&value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_lenient_tolerates_schema_drift() {
+        let res = decode(json!({
+            "results_returned": 3,
+            "results_available": 3,
+            "results_start": 1,
+            "events": [
+                {
+                    "event_id": 1,
+                    "title": "Rust.Tokyo",
+                    "event_type": "hybrid",
+                    "lat": 35.68,
+                    "lon": "139.76",
+                    "limit": "100",
+                    "thumbnail_url": "https://example.com/1.png",
+                },
+                { "title": "no id" },
+                "broken",
+            ],
+        }));
+
+        assert_eq!(res.events().len(), 1);
+        let event = &res.events()[0];
+        assert_eq!(event.title(), Some("Rust.Tokyo"));
+        assert_eq!(
+            event.event_type(),
+            Some(&EventType::Unknown("hybrid".to_string()))
+        );
+        assert_eq!(event.lat(), None);
+        assert_eq!(event.lon(), Some("139.76"));
+        assert_eq!(event.limit(), None);
+        assert_eq!(
+            event.extra().get("thumbnail_url"),
+            Some(&json!("https://example.com/1.png"))
+        );
+        assert_eq!(
+            serde_json::to_value(event).unwrap()["thumbnail_url"],
+            json!("https://example.com/1.png")
+        );
+
+        let paths: Vec<&str> = res.warnings().iter().map(|w| w.path()).collect();
+        assert_eq!(
+            paths,
+            vec!["events[0].limit", "events[0].lat", "events[1]", "events[2]"]
+        );
+    }
+
+    #[test]
+    fn test_lenient_requires_top_level_fields() {
+        let bytes = serde_json::to_vec(&json!({ "results_returned": 0, "events": [] })).unwrap();
+        assert!(ConnpassResponse::from_slice_lenient(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_strict_rejects_unknown_event_type() {
+        let res = serde_json::from_value::<ConnpassResponse>(json!({
+            "results_returned": 1,
+            "results_available": 1,
+            "results_start": 1,
+            "events": [{ "event_id": 1, "event_type": "hybrid" }],
+        }));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_unknown_event_type_round_trip() {
+        let res = decode(json!({
+            "results_returned": 1,
+            "results_available": 1,
+            "results_start": 1,
+            "events": [{ "event_id": 1, "event_type": "hybrid" }],
+        }));
+        let event = &res.events()[0];
+        let restored: Event = serde_json::from_value(serde_json::to_value(event).unwrap()).unwrap();
+        assert_eq!(&restored, event);
+
+        let old: Event =
+            serde_json::from_value(json!({ "event_id": 1, "event_type": "participation" }))
+                .unwrap();
+        let changes = diff(&old, event).unwrap();
+        let restored: EventDiff =
+            serde_json::from_value(serde_json::to_value(&changes).unwrap()).unwrap();
+        assert_eq!(restored, changes);
+
+        // The strict mode still rejects the response.
+        assert!(
+            serde_json::from_value::<ConnpassResponse>(serde_json::to_value(&res).unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_strict_ignores_unknown_fields() {
+        let res: ConnpassResponse = serde_json::from_value(json!({
+            "results_returned": 1,
+            "results_available": 1,
+            "results_start": 1,
+            "events": [{ "event_id": 1, "event_type": "participation", "thumbnail_url": "x" }],
+        }))
+        .unwrap();
+        let event = &res.events()[0];
+        assert_eq!(event.event_type(), Some(&EventType::Participation));
+        assert!(event.extra().is_empty());
+        assert!(res.warnings().is_empty());
+    }
+}
@@ -0,0 +1,516 @@
+//! Represents the response from connpass API.
+//! For more details in https://connpass.com/about/api/.
+//! The data class is along with the specification.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use self::{diff::FieldChange, lenient::DecodeWarning};
+use crate::{
+    errors::ConnpassResult,
+    id::{EventId, SeriesId, UserId},
+    url::EventUrl,
+};
+
+pub mod borrowed;
+pub mod diff;
+pub mod lenient;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct ConnpassResponse {
+    results_returned: u32,
+    results_available: u32,
+    results_start: u32,
+    #[serde(deserialize_with = "strict_events")]
+    events: Vec<Event>,
+    #[serde(skip)]
+    warnings: Vec<DecodeWarning>,
+}
+
+impl ConnpassResponse {
+    /// Decodes the response in the lenient mode, which tolerates changes of the schema. See `lenient` module for details.
+    pub fn from_slice_lenient(bytes: &[u8]) -> ConnpassResult<Self> {
+        lenient::decode(bytes)
+    }
+
+    pub fn results_returned(&self) -> u32 {
+        self.results_returned
+    }
+
+    pub fn results_available(&self) -> u32 {
+        self.results_available
+    }
+
+    pub fn results_start(&self) -> u32 {
+        self.results_start
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Consumes the response and returns the events in it.
+    pub fn into_events(self) -> Vec<Event> {
+        self.events
+    }
+
+    /// Returns the problems tolerated in the lenient mode. This is always empty in the strict (default) mode.
+    pub fn warnings(&self) -> &[DecodeWarning] {
+        &self.warnings
+    }
+}
+
+/// The result of looking up events by their ids.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct EventLookup {
+    events: HashMap<EventId, Event>,
+    missing: Vec<EventId>,
+}
+
+impl EventLookup {
+    /// Collects the events of the requested ids. The ids not found are kept in the requested order.
+    pub(crate) fn new(ids: &[EventId], found: impl IntoIterator<Item = Event>) -> Self {
+        let requested: HashSet<EventId> = ids.iter().copied().collect();
+        let events: HashMap<EventId, Event> = found
+            .into_iter()
+            .filter(|e| requested.contains(&e.event_id))
+            .map(|e| (e.event_id, e))
+            .collect();
+        let mut seen = HashSet::new();
+        let missing = ids
+            .iter()
+            .copied()
+            .filter(|id| !events.contains_key(id) && seen.insert(*id))
+            .collect();
+        EventLookup { events, missing }
+    }
+
+    /// Returns the found events keyed by their ids.
+    pub fn events(&self) -> &HashMap<EventId, Event> {
+        &self.events
+    }
+
+    pub fn into_events(self) -> HashMap<EventId, Event> {
+        self.events
+    }
+
+    /// Returns the ids whose events were not found.
+    pub fn missing(&self) -> &[EventId] {
+        &self.missing
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Event {
+    event_id: EventId,
+    title: Option<String>,
+    catch: Option<String>,
+    description: Option<String>,
+    event_url: Option<String>,
+    hash_tag: Option<String>,
+    started_at: Option<String>,
+    ended_at: Option<String>,
+    limit: Option<u32>,
+    event_type: Option<EventType>,
+    series: Option<Series>,
+    address: Option<String>,
+    place: Option<String>,
+    lat: Option<String>,
+    lon: Option<String>,
+    owner_id: Option<UserId>,
+    owner_nickname: Option<String>,
+    owner_display_name: Option<String>,
+    accepted: Option<u32>,
+    waiting: Option<u32>,
+    updated_at: Option<String>,
+    /// Fields unknown to this crate, e.g. the ones added to the API later.
+    /// Only the lenient mode fills them, to keep the strict mode fast, but they are serialized with the others.
+    #[serde(flatten, skip_deserializing)]
+    extra: BTreeMap<String, Value>,
+}
+
+/// Represents how many participants an event can accept.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Capacity {
+    /// The event has no participant limit.
+    /// connpass returns `limit` as `null` for advertisement events, and as `0` for events
+    /// that don't set any limit, so both are treated as unlimited.
+    Unlimited,
+    /// The event accepts up to the given number of participants.
+    Limited(u32),
+}
+
+impl Event {
+    pub fn id(&self) -> EventId {
+        self.event_id
+    }
+
+    #[deprecated(note = "use `Event::id`, which returns the typed id")]
+    pub fn event_id(&self) -> u32 {
+        self.event_id.get()
+    }
+
+    /// Returns `event_url` parsed, e.g. to get the subdomain of the group holding the event.
+    pub fn url(&self) -> Option<EventUrl> {
+        self.event_url.as_deref()?.parse().ok()
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn catch(&self) -> Option<&str> {
+        self.catch.as_deref()
+    }
+
+    /// Returns the description of the event. Note that this is written in HTML.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn event_url(&self) -> Option<&str> {
+        self.event_url.as_deref()
+    }
+
+    pub fn hash_tag(&self) -> Option<&str> {
+        self.hash_tag.as_deref()
+    }
+
+    pub fn started_at(&self) -> Option<&str> {
+        self.started_at.as_deref()
+    }
+
+    pub fn ended_at(&self) -> Option<&str> {
+        self.ended_at.as_deref()
+    }
+
+    /// Returns the raw `limit` value. Consider using `Event::capacity` to handle the unlimited cases.
+    pub fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    pub fn event_type(&self) -> Option<&EventType> {
+        self.event_type.as_ref()
+    }
+
+    pub fn series(&self) -> Option<&Series> {
+        self.series.as_ref()
+    }
+
+    pub fn address(&self) -> Option<&str> {
+        self.address.as_deref()
+    }
+
+    pub fn place(&self) -> Option<&str> {
+        self.place.as_deref()
+    }
+
+    pub fn lat(&self) -> Option<&str> {
+        self.lat.as_deref()
+    }
+
+    pub fn lon(&self) -> Option<&str> {
+        self.lon.as_deref()
+    }
+
+    pub fn owner_id(&self) -> Option<UserId> {
+        self.owner_id
+    }
+
+    pub fn owner_nickname(&self) -> Option<&str> {
+        self.owner_nickname.as_deref()
+    }
+
+    pub fn owner_display_name(&self) -> Option<&str> {
+        self.owner_display_name.as_deref()
+    }
+
+    pub fn accepted(&self) -> Option<u32> {
+        self.accepted
+    }
+
+    pub fn waiting(&self) -> Option<u32> {
+        self.waiting
+    }
+
+    pub fn updated_at(&self) -> Option<&str> {
+        self.updated_at.as_deref()
+    }
+
+    /// Returns the fields unknown to this crate.
+    pub fn extra(&self) -> &BTreeMap<String, Value> {
+        &self.extra
+    }
+
+    /// Returns the fields whose values differ between `self` and `other`, which must have the same `event_id`.
+    pub fn changed_fields(&self, other: &Event) -> ConnpassResult<Vec<EventField>> {
+        Ok(diff::diff(self, other)?
+            .changes()
+            .iter()
+            .map(FieldChange::field)
+            .collect())
+    }
+
+    /// Returns the capacity of the event along with the `limit` value.
+    pub fn capacity(&self) -> Capacity {
+        match self.limit {
+            None | Some(0) => Capacity::Unlimited,
+            Some(limit) => Capacity::Limited(limit),
+        }
+    }
+
+    /// Returns the number of seats left.
+    /// `None` is returned when the event is unlimited.
+    /// connpass sometimes returns `accepted` greater than `limit` (e.g. when organizers add participants by hand),
+    /// in which case this returns `Some(0)`.
+    pub fn remaining_seats(&self) -> Option<u32> {
+        match self.capacity() {
+            Capacity::Unlimited => None,
+            Capacity::Limited(limit) => Some(limit.saturating_sub(self.accepted_count())),
+        }
+    }
+
+    /// Returns the ratio of accepted participants to the limit.
+    /// `None` is returned when the event is unlimited.
+    /// Note that the ratio can exceed `1.0` when `accepted` is greater than `limit`.
+    pub fn fill_ratio(&self) -> Option<f64> {
+        match self.capacity() {
+            Capacity::Unlimited => None,
+            Capacity::Limited(limit) => Some(f64::from(self.accepted_count()) / f64::from(limit)),
+        }
+    }
+
+    /// Returns `true` if there are no seats left. Unlimited events are never full.
+    pub fn is_full(&self) -> bool {
+        self.remaining_seats() == Some(0)
+    }
+
+    /// Returns `true` if someone is on the waiting list.
+    pub fn is_waitlist_active(&self) -> bool {
+        self.waiting.unwrap_or(0) > 0
+    }
+
+    fn accepted_count(&self) -> u32 {
+        self.accepted.unwrap_or(0)
+    }
+}
+
+/// Names the fields of `Event` except for `event_id`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum EventField {
+    Title,
+    Catch,
+    Description,
+    EventUrl,
+    HashTag,
+    StartedAt,
+    EndedAt,
+    Limit,
+    EventType,
+    Series,
+    Address,
+    Place,
+    Lat,
+    Lon,
+    OwnerId,
+    OwnerNickname,
+    OwnerDisplayName,
+    Accepted,
+    Waiting,
+    UpdatedAt,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum EventType {
+    Participation,
+    Advertisement,
+    /// A value unknown to this crate. Responses with it are only decoded in the lenient mode,
+    /// but events and diffs holding it can be serialized and deserialized again.
+    Unknown(String),
+}
+
+impl EventType {
+    const VARIANTS: [&'static str; 2] = ["participation", "advertisement"];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            EventType::Participation => "participation",
+            EventType::Advertisement => "advertisement",
+            EventType::Unknown(s) => s,
+        }
+    }
+
+    fn from_lenient(s: String) -> Self {
+        match s.as_str() {
+            "participation" => EventType::Participation,
+            "advertisement" => EventType::Advertisement,
+            _ => EventType::Unknown(s),
+        }
+    }
+}
+
+impl Serialize for EventType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for EventType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(EventType::from_lenient(String::deserialize(deserializer)?))
+    }
+}
+
+/// Decodes the events of a response, rejecting the values of `event_type` unknown to this crate as the strict mode does.
+fn strict_events<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Event>, D::Error> {
+    let events = Vec::<Event>::deserialize(deserializer)?;
+    check_event_types(events.iter().map(|e| e.event_type.as_ref()))?;
+    Ok(events)
+}
+
+/// Fails on the first value of `event_type` unknown to this crate.
+fn check_event_types<'e, E: de::Error>(
+    mut event_types: impl Iterator<Item = Option<&'e EventType>>,
+) -> Result<(), E> {
+    match event_types.find_map(|t| match t {
+        Some(EventType::Unknown(s)) => Some(s),
+        _ => None,
+    }) {
+        Some(s) => Err(de::Error::unknown_variant(s, &EventType::VARIANTS)),
+        None => Ok(()),
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Series {
+    id: SeriesId,
+    title: Option<String>,
+    url: Option<String>,
+}
+
+impl Series {
+    pub fn id(&self) -> SeriesId {
+        self.id
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::id::EventId;
+
+    use super::{Capacity, Event, EventField, EventLookup};
+
+    fn event(limit: Option<u32>, accepted: Option<u32>, waiting: Option<u32>) -> Event {
+        Event {
+            event_id: EventId::new(1),
+            title: None,
+            catch: None,
+            description: None,
+            event_url: None,
+            hash_tag: None,
+            started_at: None,
+            ended_at: None,
+            limit,
+            event_type: None,
+            series: None,
+            address: None,
+            place: None,
+            lat: None,
+            lon: None,
+            owner_id: None,
+            owner_nickname: None,
+            owner_display_name: None,
+            accepted,
+            waiting,
+            updated_at: None,
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_capacity() {
+        assert_eq!(
+            event(None, Some(0), Some(0)).capacity(),
+            Capacity::Unlimited
+        );
+        assert_eq!(
+            event(Some(0), Some(3), Some(0)).capacity(),
+            Capacity::Unlimited
+        );
+        assert_eq!(
+            event(Some(30), Some(3), Some(0)).capacity(),
+            Capacity::Limited(30)
+        );
+    }
+
+    #[test]
+    fn test_remaining_seats() {
+        assert_eq!(event(None, Some(10), None).remaining_seats(), None);
+        assert_eq!(event(Some(0), Some(10), None).remaining_seats(), None);
+        assert_eq!(event(Some(30), Some(10), None).remaining_seats(), Some(20));
+        assert_eq!(event(Some(30), None, None).remaining_seats(), Some(30));
+        assert_eq!(event(Some(30), Some(35), None).remaining_seats(), Some(0));
+    }
+
+    #[test]
+    fn test_fill_ratio() {
+        assert_eq!(event(None, Some(10), None).fill_ratio(), None);
+        assert_eq!(event(Some(40), Some(10), None).fill_ratio(), Some(0.25));
+        assert_eq!(event(Some(40), None, None).fill_ratio(), Some(0.0));
+        assert_eq!(event(Some(10), Some(15), None).fill_ratio(), Some(1.5));
+    }
+
+    #[test]
+    fn test_is_full() {
+        assert!(!event(None, Some(100), None).is_full());
+        assert!(!event(Some(0), Some(100), None).is_full());
+        assert!(!event(Some(30), Some(29), None).is_full());
+        assert!(event(Some(30), Some(30), None).is_full());
+        assert!(event(Some(30), Some(31), None).is_full());
+    }
+
+    #[test]
+    fn test_changed_fields() {
+        let old = event(Some(30), Some(30), Some(2));
+        assert!(old.changed_fields(&old.clone()).unwrap().is_empty());
+
+        let mut new = event(Some(30), Some(29), Some(2));
+        new.title = Some("Rust.Tokyo".to_string());
+        assert_eq!(
+            old.changed_fields(&new).unwrap(),
+            vec![EventField::Title, EventField::Accepted]
+        );
+    }
+
+    #[test]
+    fn test_is_waitlist_active() {
+        assert!(!event(Some(30), Some(30), None).is_waitlist_active());
+        assert!(!event(Some(30), Some(30), Some(0)).is_waitlist_active());
+        assert!(event(Some(30), Some(30), Some(5)).is_waitlist_active());
+    }
+
+    #[test]
+    fn test_event_lookup() {
+        let found = [3, 1, 99].iter().map(|&id| Event {
+            event_id: EventId::new(id),
+            ..event(None, None, None)
+        });
+        let ids: Vec<EventId> = [1, 2, 3, 4, 2].iter().map(|&id| EventId::new(id)).collect();
+        let lookup = EventLookup::new(&ids, found);
+        let mut found: Vec<u32> = lookup.events().keys().map(|id| id.get()).collect();
+        found.sort_unstable();
+        assert_eq!(found, vec![1, 3]);
+        assert_eq!(lookup.missing(), [EventId::new(2), EventId::new(4)]);
+    }
+}
@@ -0,0 +1,398 @@
+//! Schedules requests from many callers sharing a client, so that interactive requests don't queue behind long crawls.
+//!
+//! Requests are sent one by one with an interval between them.
+//! 1. interactive requests are sent before background ones, but a background request is sent
+//!    after a number of interactive ones in a row (4 by default), so that crawls keep progressing.
+//! 2. within each priority, callers take turns, so that a caller queueing many requests can't block the others.
+
+use std::{collections::VecDeque, future::Future, sync::Arc, time::Duration};
+
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+    time::{self, Instant},
+};
+
+use crate::{
+    client::ConnpassClient,
+    errors::{ConnpassCliError, ConnpassResult, HttpResponseError},
+    query::Query,
+    response::ConnpassResponse,
+};
+
+const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_INTERACTIVE_BURST: usize = 4;
+
+/// The priority class of a request.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Priority {
+    /// For requests someone is waiting for, e.g. lookups by a bot.
+    Interactive,
+    /// For requests nobody is waiting for, e.g. nightly crawls.
+    Background,
+}
+
+/// Settings of the scheduler. Call `start` to run it.
+///
+/// # Example:
+/// ```no_run
+/// use connpass_rs::{
+///     client::ConnpassClient,
+///     connpass_query,
+///     scheduler::{Priority, Scheduler},
+/// };
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let scheduler = Scheduler::new(ConnpassClient::new()).start();
+///     let query = connpass_query! { keyword: "Rust" }.unwrap();
+///     let res = scheduler
+///         .send_request("bot", Priority::Interactive, query)
+///         .await;
+///     println!("{:?}", res);
+/// }
+/// ```
+pub struct Scheduler {
+    client: ConnpassClient,
+    min_interval: Duration,
+    interactive_burst: usize,
+}
+
+impl Scheduler {
+    /// Initializes `Scheduler`. The requests are sent at intervals of 1 second by default.
+    pub fn new(client: ConnpassClient) -> Self {
+        Scheduler {
+            client,
+            min_interval: DEFAULT_MIN_INTERVAL,
+            interactive_burst: DEFAULT_INTERACTIVE_BURST,
+        }
+    }
+
+    /// Sets the minimum interval between the starts of requests.
+    pub fn min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Sets how many interactive requests can be sent in a row while background ones are waiting. 0 is treated as 1.
+    pub fn interactive_burst(mut self, interactive_burst: usize) -> Self {
+        self.interactive_burst = interactive_burst.max(1);
+        self
+    }
+
+    /// Starts sending the requests on a task of the tokio runtime, so this has to be called within it.
+    /// The task stops after every handle is dropped and the queued requests are sent.
+    pub fn start(self) -> SchedulerHandle {
+        let client = self.client.clone();
+        let (handle, _) = self.start_with(move |query| {
+            let client = client.clone();
+            async move { client.send_request(query).await }
+        });
+        handle
+    }
+
+    /// Starts the task sending the requests by `send`, which is replaced in tests.
+    fn start_with<F, Fut>(&self, send: F) -> (SchedulerHandle, JoinHandle<()>)
+    where
+        F: FnMut(Query) -> Fut + Send + 'static,
+        Fut: Future<Output = ConnpassResult<ConnpassResponse>> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(run(
+            self.min_interval,
+            self.interactive_burst,
+            receiver,
+            send,
+        ));
+        (SchedulerHandle { sender }, task)
+    }
+}
+
+async fn run<F, Fut>(
+    min_interval: Duration,
+    interactive_burst: usize,
+    mut receiver: mpsc::UnboundedReceiver<Job>,
+    mut send: F,
+) where
+    F: FnMut(Query) -> Fut,
+    Fut: Future<Output = ConnpassResult<ConnpassResponse>>,
+{
+    let mut queues = PriorityQueues::new(interactive_burst);
+    let mut next_send = Instant::now();
+    loop {
+        if queues.is_empty() {
+            match receiver.recv().await {
+                Some(job) => queues.push(job.caller.clone(), job.priority, job),
+                None => return,
+            }
+        }
+        // Waits before choosing the job, so that the jobs queued in the meantime are taken into account.
+        time::sleep_until(next_send).await;
+        while let Ok(job) = receiver.try_recv() {
+            queues.push(job.caller.clone(), job.priority, job);
+        }
+        let job = match queues.pop() {
+            Some(job) => job,
+            None => continue,
+        };
+        // The caller has stopped waiting, so the request doesn't have to be sent.
+        if job.reply.is_closed() {
+            continue;
+        }
+        next_send = Instant::now() + min_interval;
+        let res = send(job.query).await;
+        // The caller may have stopped waiting in the meantime, so the result can be dropped.
+        let _ = job.reply.send(res);
+    }
+}
+
+/// A handle to send requests through the scheduler. Clone it to share among callers.
+#[derive(Clone)]
+pub struct SchedulerHandle {
+    sender: mpsc::UnboundedSender<Job>,
+}
+
+impl SchedulerHandle {
+    /// Queues the request, and waits for its response.
+    /// `caller` identifies who sends it, e.g. the name of a job, and the callers with the same priority take turns.
+    pub async fn send_request(
+        &self,
+        caller: &str,
+        priority: Priority,
+        query: Query,
+    ) -> ConnpassResult<ConnpassResponse> {
+        let (reply, response) = oneshot::channel();
+        let job = Job {
+            caller: Arc::from(caller),
+            priority,
+            query,
+            reply,
+        };
+        self.sender.send(job).map_err(|_| stopped())?;
+        response.await.map_err(|_| stopped())?
+    }
+}
+
+struct Job {
+    caller: Arc<str>,
+    priority: Priority,
+    query: Query,
+    reply: oneshot::Sender<ConnpassResult<ConnpassResponse>>,
+}
+
+fn stopped() -> ConnpassCliError {
+    ConnpassCliError::HttpResponse(HttpResponseError::Various(
+        "The scheduler has stopped".to_string(),
+    ))
+}
+
+/// The queues of the priorities. See the module document for the order of popping.
+struct PriorityQueues<T> {
+    interactive: FairQueue<T>,
+    background: FairQueue<T>,
+    interactive_burst: usize,
+    interactive_streak: usize,
+}
+
+impl<T> PriorityQueues<T> {
+    fn new(interactive_burst: usize) -> Self {
+        PriorityQueues {
+            interactive: FairQueue::default(),
+            background: FairQueue::default(),
+            interactive_burst,
+            interactive_streak: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.interactive.is_empty() && self.background.is_empty()
+    }
+
+    fn push(&mut self, caller: Arc<str>, priority: Priority, item: T) {
+        match priority {
+            Priority::Interactive => self.interactive.push(caller, item),
+            Priority::Background => self.background.push(caller, item),
+        }
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let background_turn =
+            self.interactive.is_empty() || self.interactive_streak >= self.interactive_burst;
+        if background_turn && !self.background.is_empty() {
+            self.interactive_streak = 0;
+            return self.background.pop();
+        }
+        let item = self.interactive.pop()?;
+        self.interactive_streak += 1;
+        Some(item)
+    }
+}
+
+/// A queue where callers take turns.
+struct FairQueue<T> {
+    callers: VecDeque<(Arc<str>, VecDeque<T>)>,
+}
+
+impl<T> Default for FairQueue<T> {
+    fn default() -> Self {
+        FairQueue {
+            callers: VecDeque::new(),
+        }
+    }
+}
+
+impl<T> FairQueue<T> {
+    fn is_empty(&self) -> bool {
+        self.callers.is_empty()
+    }
+
+    fn push(&mut self, caller: Arc<str>, item: T) {
+        match self.callers.iter_mut().find(|(c, _)| *c == caller) {
+            Some((_, items)) => items.push_back(item),
+            None => self.callers.push_back((caller, VecDeque::from(vec![item]))),
+        }
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let (caller, mut items) = self.callers.pop_front()?;
+        let item = items.pop_front();
+        if !items.is_empty() {
+            self.callers.push_back((caller, items));
+        }
+        item
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use serde_json::json;
+    use tokio::{
+        task::JoinHandle,
+        time::{self, Instant},
+    };
+
+    use crate::{client::ConnpassClient, query::builder::QueryBuilder};
+
+    use super::{FairQueue, Priority, PriorityQueues, Scheduler, SchedulerHandle};
+
+    /// The keywords of the requests sent, and the time since the start when each of them was sent.
+    type Sent = Arc<Mutex<Vec<(String, Duration)>>>;
+
+    /// Starts a scheduler which records the keyword of each request and the time it's sent at instead of sending it.
+    fn start() -> (SchedulerHandle, JoinHandle<()>, Sent) {
+        let started = Instant::now();
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let recorder = sent.clone();
+        let (handle, task) = Scheduler::new(ConnpassClient::new())
+            .min_interval(Duration::from_secs(1))
+            .start_with(move |query| {
+                recorder
+                    .lock()
+                    .unwrap()
+                    .push((query.keywords()[0].clone(), started.elapsed()));
+                async {
+                    Ok(serde_json::from_value(json!({
+                        "results_returned": 0,
+                        "results_available": 0,
+                        "results_start": 1,
+                        "events": [],
+                    }))
+                    .unwrap())
+                }
+            });
+        (handle, task, sent)
+    }
+
+    fn spawn_request(
+        handle: &SchedulerHandle,
+        caller: &'static str,
+        priority: Priority,
+        keyword: &'static str,
+    ) -> JoinHandle<()> {
+        let handle = handle.clone();
+        tokio::spawn(async move {
+            let query = QueryBuilder::begin().keyword(keyword).build().unwrap();
+            handle.send_request(caller, priority, query).await.unwrap();
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_spaces_and_prioritizes_requests() {
+        let (handle, task, sent) = start();
+        let mut requests = vec![
+            spawn_request(&handle, "crawl", Priority::Background, "c1"),
+            spawn_request(&handle, "crawl", Priority::Background, "c2"),
+        ];
+        time::sleep(Duration::from_millis(100)).await;
+        requests.push(spawn_request(&handle, "bot", Priority::Interactive, "i1"));
+        for request in requests {
+            request.await.unwrap();
+        }
+
+        // The scheduler stops after the handles are dropped.
+        drop(handle);
+        task.await.unwrap();
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![
+                ("c1".to_string(), Duration::ZERO),
+                ("i1".to_string(), Duration::from_secs(1)),
+                ("c2".to_string(), Duration::from_secs(2)),
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_skips_abandoned_requests() {
+        let (handle, task, sent) = start();
+        let first = spawn_request(&handle, "bot", Priority::Interactive, "i1");
+        let abandoned = spawn_request(&handle, "bot", Priority::Interactive, "i2");
+        time::sleep(Duration::from_millis(100)).await;
+        abandoned.abort();
+        spawn_request(&handle, "bot", Priority::Interactive, "i3")
+            .await
+            .unwrap();
+        first.await.unwrap();
+
+        drop(handle);
+        task.await.unwrap();
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![
+                ("i1".to_string(), Duration::ZERO),
+                ("i3".to_string(), Duration::from_secs(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fair_queue_takes_turns() {
+        let mut queue = FairQueue::default();
+        for item in ["a1", "a2", "a3"] {
+            queue.push(Arc::from("a"), item);
+        }
+        queue.push(Arc::from("b"), "b1");
+        queue.push(Arc::from("b"), "b2");
+        let popped: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(popped, vec!["a1", "b1", "a2", "b2", "a3"]);
+    }
+
+    #[test]
+    fn test_priority_queues_keep_background_progressing() {
+        let mut queues = PriorityQueues::new(2);
+        for i in 0..3 {
+            queues.push(Arc::from("crawl"), Priority::Background, format!("c{}", i));
+        }
+        for i in 0..5 {
+            queues.push(Arc::from("bot"), Priority::Interactive, format!("i{}", i));
+        }
+        let popped: Vec<_> = std::iter::from_fn(|| queues.pop()).collect();
+        assert_eq!(popped, vec!["i0", "i1", "c0", "i2", "i3", "c1", "i4", "c2"]);
+        assert!(queues.is_empty());
+    }
+}
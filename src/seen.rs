@@ -0,0 +1,413 @@
+//! Tracks which events have been seen, so that e.g. a cron job notifies only the events new since its last run.
+//!
+//! The state is kept by a `SeenStore`, e.g. `FileStore`, and advanced only by `Changes::commit` or `Changes::commit_events`.
+//! Commit after the changes are handled (e.g. posted), then:
+//! 1. if the job crashes before committing, the same changes are returned again on the next run, so nothing is lost.
+//!    Committing each event right after handling it narrows what can be handled twice down to that event.
+//! 2. a commit merges its events into the latest saved state, so commits of different `Changes` don't overwrite each other.
+//! 3. `FileStore` replaces the file atomically, so a crash while committing leaves either the old or the new state.
+//!
+//! Commits are serialized only within a `SeenTracker`, i.e. within a process.
+//! `FileStore` doesn't lock the file, so when processes share it, commits running at the same time can overwrite
+//! each other and the lost events are returned again on the next run. Don't run such jobs concurrently.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, MutexGuard,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::ConnpassClient,
+    errors::{ConnpassCliError, ConnpassResult, StoreError},
+    id::EventId,
+    query::Query,
+    response::Event,
+};
+
+/// `updated_at` of the events seen so far.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct SeenState {
+    events: BTreeMap<EventId, Option<String>>,
+}
+
+impl SeenState {
+    /// Returns `true` if the event is not seen yet, or its `updated_at` has changed since then.
+    pub fn is_changed(&self, event: &Event) -> bool {
+        match self.events.get(&event.id()) {
+            Some(updated_at) => updated_at.as_deref() != event.updated_at(),
+            None => true,
+        }
+    }
+
+    pub fn mark_seen(&mut self, event: &Event) {
+        self.events
+            .insert(event.id(), event.updated_at().map(str::to_string));
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+/// Where `SeenState` is persisted.
+pub trait SeenStore {
+    /// Loads the state. If nothing is saved yet, this returns the empty state.
+    fn load(&self) -> ConnpassResult<SeenState>;
+
+    /// Replaces the saved state. The replacement should be atomic, i.e. either all or nothing is saved.
+    fn save(&self, state: &SeenState) -> ConnpassResult<()>;
+}
+
+/// Saves the state as a JSON file.
+/// The file is replaced by writing a temporary file next to it and renaming it, so it's never left half-written.
+/// It isn't locked, so it shouldn't be committed to by several processes at the same time.
+#[derive(Clone, Debug)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileStore { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a path next to the file, unique to the process and the call,
+    /// so that concurrent saves don't write into the same temporary file.
+    fn temp_path(&self) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(
+            ".{}.{}.tmp",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        self.path.with_file_name(name)
+    }
+}
+
+impl SeenStore for FileStore {
+    fn load(&self) -> ConnpassResult<SeenState> {
+        match fs::read(&self.path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|err| json_error(err.to_string())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(SeenState::default()),
+            Err(err) => Err(io_error(err)),
+        }
+    }
+
+    fn save(&self, state: &SeenState) -> ConnpassResult<()> {
+        let bytes = serde_json::to_vec(state).map_err(|err| json_error(err.to_string()))?;
+        let temp_path = self.temp_path();
+        let written = fs::File::create(&temp_path).and_then(|mut file| {
+            file.write_all(&bytes)?;
+            file.sync_all()
+        });
+        if let Err(err) = written.and_then(|_| fs::rename(&temp_path, &self.path)) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(io_error(err));
+        }
+        // Syncs the directory as well, otherwise the rename can be lost by a power loss.
+        sync_dir(&self.path).map_err(io_error)
+    }
+}
+
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Keeps the state in memory, e.g. for tests.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    state: Mutex<SeenState>,
+}
+
+impl SeenStore for MemoryStore {
+    fn load(&self) -> ConnpassResult<SeenState> {
+        Ok(self.lock().clone())
+    }
+
+    fn save(&self, state: &SeenState) -> ConnpassResult<()> {
+        *self.lock() = state.clone();
+        Ok(())
+    }
+}
+
+impl MemoryStore {
+    fn lock(&self) -> MutexGuard<'_, SeenState> {
+        // The state is only replaced as a whole, so it's consistent even if a thread panicked holding the lock.
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Finds the events new or updated since the last commit with the store.
+///
+/// # Example:
+/// ```no_run
+/// use connpass_rs::{
+///     client::ConnpassClient,
+///     connpass_query,
+///     seen::{FileStore, SeenTracker},
+/// };
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let tracker = SeenTracker::new(FileStore::new("seen.json"));
+///     let query = connpass_query! { keyword: "Rust", order: Newer, count: 100 }.unwrap();
+///     let changes = tracker.check(&ConnpassClient::new(), query).await.unwrap();
+///     for event in changes.events() {
+///         println!("{:?}", event.title());
+///     }
+///     changes.commit().unwrap();
+/// }
+/// ```
+pub struct SeenTracker<S> {
+    store: S,
+    // Serializes the commits, which load the state, merge into it and save it.
+    commit_lock: Mutex<()>,
+}
+
+impl<S: SeenStore> SeenTracker<S> {
+    pub fn new(store: S) -> Self {
+        SeenTracker {
+            store,
+            commit_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Sends the query, and returns the events new or updated since the last commit.
+    /// Only the page specified by the query is checked.
+    pub async fn check(
+        &self,
+        client: &ConnpassClient,
+        query: Query,
+    ) -> ConnpassResult<Changes<'_, S>> {
+        let res = client.send_request(query).await?;
+        self.diff(res.into_events())
+    }
+
+    /// Returns the events new or updated since the last commit among the fetched ones.
+    /// This is for the events fetched in other ways, e.g. by the blocking client.
+    pub fn diff(&self, events: Vec<Event>) -> ConnpassResult<Changes<'_, S>> {
+        let mut state = self.store.load()?;
+        // Marks each event as soon as it's found, so that it's returned once even if fetched twice.
+        let events = events
+            .into_iter()
+            .filter(|event| {
+                let changed = state.is_changed(event);
+                if changed {
+                    state.mark_seen(event);
+                }
+                changed
+            })
+            .collect();
+        Ok(Changes {
+            tracker: self,
+            events,
+        })
+    }
+
+    /// Marks the events as seen in the latest saved state, and saves it.
+    fn commit(&self, events: &[&Event]) -> ConnpassResult<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let _guard = self.lock();
+        let mut state = self.store.load()?;
+        for event in events {
+            state.mark_seen(event);
+        }
+        self.store.save(&state)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ()> {
+        // The lock guards no data, so it's usable even if another commit panicked.
+        self.commit_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The events new or updated since the last commit. Call `commit` after handling them to save them as seen.
+#[must_use = "the events are not saved as seen until `commit` is called"]
+pub struct Changes<'t, S: SeenStore> {
+    tracker: &'t SeenTracker<S>,
+    events: Vec<Event>,
+}
+
+impl<'t, S: SeenStore> Changes<'t, S> {
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Saves the events as seen, so that they are not returned again unless they are updated.
+    pub fn commit(self) -> ConnpassResult<()> {
+        self.tracker.commit(&self.events.iter().collect::<Vec<_>>())
+    }
+
+    /// Saves only the events of the ids among these changes as seen, e.g. right after each of them is posted.
+    pub fn commit_events(&self, event_ids: &[EventId]) -> ConnpassResult<()> {
+        let events: Vec<&Event> = self
+            .events
+            .iter()
+            .filter(|event| event_ids.contains(&event.id()))
+            .collect();
+        self.tracker.commit(&events)
+    }
+}
+
+fn io_error(err: std::io::Error) -> ConnpassCliError {
+    ConnpassCliError::Store(StoreError::Io(err))
+}
+
+fn json_error(msg: String) -> ConnpassCliError {
+    ConnpassCliError::Store(StoreError::Json(msg))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use serde_json::json;
+
+    use crate::{id::EventId, response::Event};
+
+    use super::{FileStore, MemoryStore, SeenState, SeenStore, SeenTracker};
+
+    fn event(id: u32, updated_at: &str) -> Event {
+        serde_json::from_value(json!({ "event_id": id, "updated_at": updated_at })).unwrap()
+    }
+
+    fn ids(events: &[Event]) -> Vec<u32> {
+        events.iter().map(|e| e.id().get()).collect()
+    }
+
+    #[test]
+    fn test_tracker_returns_new_and_updated_events() {
+        let tracker = SeenTracker::new(MemoryStore::default());
+        let changes = tracker
+            .diff(vec![event(1, "2021-10-01"), event(2, "2021-10-01")])
+            .unwrap();
+        assert_eq!(ids(changes.events()), vec![1, 2]);
+        changes.commit().unwrap();
+
+        let changes = tracker
+            .diff(vec![
+                event(1, "2021-10-01"),
+                event(2, "2021-10-02"),
+                event(3, "2021-10-02"),
+            ])
+            .unwrap();
+        assert_eq!(ids(changes.events()), vec![2, 3]);
+        // Not committed, e.g. because the job crashed, so the same events are returned again.
+        drop(changes);
+
+        let changes = tracker
+            .diff(vec![event(2, "2021-10-02"), event(3, "2021-10-02")])
+            .unwrap();
+        assert_eq!(ids(changes.events()), vec![2, 3]);
+        changes.commit().unwrap();
+
+        assert!(tracker
+            .diff(vec![event(1, "2021-10-01")])
+            .unwrap()
+            .is_empty());
+        assert_eq!(tracker.store().load().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_interleaved_commits_are_merged() {
+        let tracker = SeenTracker::new(MemoryStore::default());
+        let first = tracker.diff(vec![event(1, "2021-10-01")]).unwrap();
+        let second = tracker.diff(vec![event(2, "2021-10-01")]).unwrap();
+        first.commit().unwrap();
+        second.commit().unwrap();
+        assert!(tracker
+            .diff(vec![event(1, "2021-10-01"), event(2, "2021-10-01")])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_commit_events() {
+        let tracker = SeenTracker::new(MemoryStore::default());
+        let changes = tracker
+            .diff(vec![event(1, "2021-10-01"), event(2, "2021-10-01")])
+            .unwrap();
+        changes.commit_events(&[EventId::new(1)]).unwrap();
+        // Crashed before committing the rest, so only the event 2 is returned again.
+        drop(changes);
+
+        let changes = tracker
+            .diff(vec![event(1, "2021-10-01"), event(2, "2021-10-01")])
+            .unwrap();
+        assert_eq!(ids(changes.events()), vec![2]);
+    }
+
+    #[test]
+    fn test_memory_store_recovers_from_poisoned_lock() {
+        let store = MemoryStore::default();
+        let _ = std::panic::catch_unwind(|| {
+            let _guard = store.state.lock().unwrap();
+            panic!("poison the lock");
+        });
+        assert!(store.state.is_poisoned());
+
+        let mut state = SeenState::default();
+        state.mark_seen(&event(1, "2021-10-01"));
+        store.save(&state).unwrap();
+        assert_eq!(store.load().unwrap(), state);
+    }
+
+    #[test]
+    fn test_file_store() {
+        let dir = std::env::temp_dir().join(format!("connpass-rs-seen-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let store = FileStore::new(dir.join("seen.json"));
+        assert!(store.load().unwrap().is_empty());
+
+        let mut state = SeenState::default();
+        state.mark_seen(&event(1, "2021-10-01"));
+        store.save(&state).unwrap();
+        assert_eq!(store.load().unwrap(), state);
+        // No temporary file is left.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,277 @@
+//! Parses connpass URLs into typed identifiers.
+//! The following URLs are recognized:
+//! 1. event URLs like `https://rust.connpass.com/event/228732/` or `https://connpass.com/event/228732/`.
+//! 2. group URLs like `https://rust.connpass.com/`.
+//! 3. user profile URLs like `https://connpass.com/user/yuk1ty/`.
+
+use std::{fmt, str::FromStr};
+
+use reqwest::Url;
+
+use crate::{
+    errors::{ConnpassCliError, ConnpassResult, ValidationError},
+    id::EventId,
+};
+
+/// Hosts that belong to connpass itself, not to groups.
+const CONNPASS_HOSTS: [&str; 2] = ["connpass.com", "www.connpass.com"];
+/// Subdomains reserved by connpass, which are never group subdomains.
+const RESERVED_SUBDOMAINS: [&str; 2] = ["api", "help"];
+
+/// Represents an event URL.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct EventUrl {
+    subdomain: Option<String>,
+    event_id: EventId,
+}
+
+impl EventUrl {
+    /// Returns the group subdomain, or `None` if the URL is under connpass.com directly.
+    pub fn subdomain(&self) -> Option<&str> {
+        self.subdomain.as_deref()
+    }
+
+    pub fn event_id(&self) -> EventId {
+        self.event_id
+    }
+}
+
+/// Represents a group URL.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct GroupUrl {
+    subdomain: String,
+}
+
+impl GroupUrl {
+    pub fn subdomain(&self) -> &str {
+        &self.subdomain
+    }
+}
+
+/// Represents a user profile URL.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct UserUrl {
+    nickname: String,
+}
+
+impl UserUrl {
+    pub fn nickname(&self) -> &str {
+        &self.nickname
+    }
+}
+
+/// Any of the URLs recognized in this module.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum ConnpassUrl {
+    Event(EventUrl),
+    Group(GroupUrl),
+    User(UserUrl),
+}
+
+impl FromStr for ConnpassUrl {
+    type Err = ConnpassCliError;
+
+    fn from_str(s: &str) -> ConnpassResult<Self> {
+        let url = Url::parse(s).map_err(|_| invalid(s))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(invalid(s));
+        }
+        let host = url.host_str().ok_or_else(|| invalid(s))?;
+        let subdomain = if CONNPASS_HOSTS.contains(&host) {
+            None
+        } else {
+            match host.strip_suffix(".connpass.com") {
+                Some(sub) if !sub.contains('.') && !RESERVED_SUBDOMAINS.contains(&sub) => {
+                    Some(sub.to_string())
+                }
+                _ => return Err(invalid(s)),
+            }
+        };
+        let segments: Vec<&str> = url
+            .path_segments()
+            .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+
+        match (subdomain, segments.as_slice()) {
+            (subdomain, ["event", id, ..]) => {
+                let event_id = id.parse().map_err(|_| invalid(s))?;
+                Ok(ConnpassUrl::Event(EventUrl {
+                    subdomain,
+                    event_id,
+                }))
+            }
+            (None, ["user", nickname, ..]) => Ok(ConnpassUrl::User(UserUrl {
+                nickname: nickname.to_string(),
+            })),
+            (Some(subdomain), _) => Ok(ConnpassUrl::Group(GroupUrl { subdomain })),
+            (None, _) => Err(invalid(s)),
+        }
+    }
+}
+
+impl FromStr for EventUrl {
+    type Err = ConnpassCliError;
+
+    fn from_str(s: &str) -> ConnpassResult<Self> {
+        match s.parse()? {
+            ConnpassUrl::Event(url) => Ok(url),
+            _ => Err(invalid(s)),
+        }
+    }
+}
+
+impl FromStr for GroupUrl {
+    type Err = ConnpassCliError;
+
+    fn from_str(s: &str) -> ConnpassResult<Self> {
+        match s.parse()? {
+            ConnpassUrl::Group(url) => Ok(url),
+            _ => Err(invalid(s)),
+        }
+    }
+}
+
+impl FromStr for UserUrl {
+    type Err = ConnpassCliError;
+
+    fn from_str(s: &str) -> ConnpassResult<Self> {
+        match s.parse()? {
+            ConnpassUrl::User(url) => Ok(url),
+            _ => Err(invalid(s)),
+        }
+    }
+}
+
+/// Formats the canonical URL like `https://rust.connpass.com/event/228732/`.
+impl fmt::Display for EventUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.subdomain {
+            Some(subdomain) => write!(
+                f,
+                "https://{}.connpass.com/event/{}/",
+                subdomain, self.event_id
+            ),
+            None => write!(f, "https://connpass.com/event/{}/", self.event_id),
+        }
+    }
+}
+
+/// Formats the canonical URL like `https://rust.connpass.com/`.
+impl fmt::Display for GroupUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "https://{}.connpass.com/", self.subdomain)
+    }
+}
+
+/// Formats the canonical URL like `https://connpass.com/user/yuk1ty/`.
+impl fmt::Display for UserUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "https://connpass.com/user/{}/", self.nickname)
+    }
+}
+
+fn invalid(s: &str) -> ConnpassCliError {
+    ConnpassCliError::Validation(ValidationError::InvalidToken {
+        msg: format!("Unrecognized connpass URL: {}", s),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::errors::{ConnpassCliError, ValidationError};
+
+    use crate::id::EventId;
+
+    use super::{ConnpassUrl, EventUrl, GroupUrl, UserUrl};
+
+    #[test]
+    fn test_parse_event_url() {
+        let url: EventUrl = "https://rust.connpass.com/event/228732/".parse().unwrap();
+        assert_eq!(url.subdomain(), Some("rust"));
+        assert_eq!(url.event_id(), EventId::new(228732));
+
+        let url: EventUrl = "https://connpass.com/event/228732/participation/"
+            .parse()
+            .unwrap();
+        assert_eq!(url.subdomain(), None);
+        assert_eq!(url.event_id(), EventId::new(228732));
+
+        let url: EventUrl = "http://rust.connpass.com/event/228732?utm_source=x"
+            .parse()
+            .unwrap();
+        assert_eq!(url.event_id(), EventId::new(228732));
+    }
+
+    #[test]
+    fn test_parse_group_url() {
+        let url: GroupUrl = "https://rust.connpass.com/".parse().unwrap();
+        assert_eq!(url.subdomain(), "rust");
+
+        let url: GroupUrl = "https://rust.connpass.com/event/".parse().unwrap();
+        assert_eq!(url.subdomain(), "rust");
+    }
+
+    #[test]
+    fn test_parse_user_url() {
+        let url: UserUrl = "https://connpass.com/user/yuk1ty/".parse().unwrap();
+        assert_eq!(url.nickname(), "yuk1ty");
+    }
+
+    #[test]
+    fn test_parse_connpass_url() {
+        assert!(matches!(
+            "https://rust.connpass.com/event/228732/".parse(),
+            Ok(ConnpassUrl::Event(_))
+        ));
+        assert!(matches!(
+            "https://rust.connpass.com".parse(),
+            Ok(ConnpassUrl::Group(_))
+        ));
+        assert!(matches!(
+            "https://connpass.com/user/yuk1ty".parse(),
+            Ok(ConnpassUrl::User(_))
+        ));
+    }
+
+    #[test]
+    fn test_display_canonical_url() {
+        let url: EventUrl = "http://rust.connpass.com/event/228732?utm_source=x"
+            .parse()
+            .unwrap();
+        assert_eq!(url.to_string(), "https://rust.connpass.com/event/228732/");
+        let url: EventUrl = "https://www.connpass.com/event/228732".parse().unwrap();
+        assert_eq!(url.to_string(), "https://connpass.com/event/228732/");
+        let url: GroupUrl = "https://rust.connpass.com/event/".parse().unwrap();
+        assert_eq!(url.to_string(), "https://rust.connpass.com/");
+        let url: UserUrl = "https://connpass.com/user/yuk1ty".parse().unwrap();
+        assert_eq!(url.to_string(), "https://connpass.com/user/yuk1ty/");
+    }
+
+    #[test]
+    fn test_parse_unrecognized_url() {
+        for s in [
+            "228732",
+            "https://example.com/event/228732/",
+            "https://connpass.com/",
+            "https://www.connpass.com/event/",
+            "https://api.connpass.com/",
+            "https://rust.connpass.com/event/rust/",
+            "ftp://rust.connpass.com/event/228732/",
+        ] {
+            assert!(
+                matches!(
+                    s.parse::<ConnpassUrl>(),
+                    Err(ConnpassCliError::Validation(
+                        ValidationError::InvalidToken { msg: _ }
+                    ))
+                ),
+                "{}",
+                s
+            );
+        }
+        assert!("https://rust.connpass.com/".parse::<EventUrl>().is_err());
+        assert!("https://connpass.com/user/yuk1ty/"
+            .parse::<GroupUrl>()
+            .is_err());
+    }
+}
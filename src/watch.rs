@@ -0,0 +1,268 @@
+//! Watches a query by polling connpass API periodically, and notifies the changes of the events as a stream.
+//! This is useful to get notified when a seat frees up or a new event appears.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use futures::{stream, Stream};
+use tokio::time::{self, Interval, MissedTickBehavior};
+
+use crate::{
+    client::ConnpassClient,
+    errors::ConnpassResult,
+    id::EventId,
+    query::Query,
+    response::{Event, EventField},
+};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A notification emitted by `Watcher`.
+#[derive(PartialEq, Clone, Debug)]
+pub enum WatchEvent {
+    /// The event appeared in the result for the first time.
+    EventAdded(Event),
+    /// The event was in the previous result as well, but some of its fields have changed.
+    EventUpdated {
+        event: Event,
+        changed_fields: Vec<EventField>,
+    },
+    /// The event was in the previous result, but is no longer there.
+    EventRemoved(Event),
+}
+
+/// Polls a query on an interval and diffs successive results by `event_id` and their fields.
+///
+/// Note that only the page specified by the query is watched.
+/// An event falling off the page (e.g. because of `count` or `order`) is notified as `WatchEvent::EventRemoved`.
+///
+/// # Example:
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use connpass_rs::{client::ConnpassClient, query::builder::QueryBuilder, watch::Watcher};
+/// use futures::StreamExt;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let query = QueryBuilder::begin().keyword("Rust").build().unwrap();
+///     let watcher = Watcher::new(ConnpassClient::new(), query).interval(Duration::from_secs(600));
+///     let mut notifications = Box::pin(watcher.into_stream());
+///     while let Some(notification) = notifications.next().await {
+///         println!("{:?}", notification);
+///     }
+/// }
+/// ```
+pub struct Watcher {
+    client: ConnpassClient,
+    query: Query,
+    interval: Duration,
+    emit_initial: bool,
+}
+
+impl Watcher {
+    /// Initializes `Watcher`. The query is polled every 60 seconds by default.
+    pub fn new(client: ConnpassClient, query: Query) -> Self {
+        Watcher {
+            client,
+            query,
+            interval: DEFAULT_INTERVAL,
+            emit_initial: false,
+        }
+    }
+
+    /// Sets the polling interval. An interval shorter than 1 second is treated as 1 second.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(MIN_INTERVAL);
+        self
+    }
+
+    /// If `true`, the events in the first result are notified as `WatchEvent::EventAdded`.
+    /// Otherwise, the first result is just used as the baseline. Defaults to `false`.
+    pub fn emit_initial(mut self, emit_initial: bool) -> Self {
+        self.emit_initial = emit_initial;
+        self
+    }
+
+    /// Starts polling and returns the notifications as a stream.
+    /// Errors during polling are yielded as they are, and the polling continues on the next tick.
+    /// The stream never ends, so drop it to stop watching.
+    pub fn into_stream(self) -> impl Stream<Item = ConnpassResult<WatchEvent>> {
+        let state = WatchState {
+            snapshot: if self.emit_initial {
+                Some(HashMap::new())
+            } else {
+                None
+            },
+            watcher: self,
+            ticker: None,
+            pending: VecDeque::new(),
+        };
+
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(notification) = state.pending.pop_front() {
+                    return Some((Ok(notification), state));
+                }
+
+                let interval = state.watcher.interval;
+                state
+                    .ticker
+                    .get_or_insert_with(|| make_ticker(interval))
+                    .tick()
+                    .await;
+
+                match state
+                    .watcher
+                    .client
+                    .send_request(state.watcher.query.clone())
+                    .await
+                {
+                    Ok(res) => {
+                        let current = res.into_events();
+                        match state.snapshot.take() {
+                            Some(previous) => {
+                                let (notifications, snapshot) = diff_snapshot(previous, current);
+                                state.pending.extend(notifications);
+                                state.snapshot = Some(snapshot);
+                            }
+                            None => state.snapshot = Some(make_snapshot(current)),
+                        }
+                    }
+                    Err(err) => return Some((Err(err), state)),
+                }
+            }
+        })
+    }
+}
+
+struct WatchState {
+    watcher: Watcher,
+    ticker: Option<Interval>,
+    snapshot: Option<HashMap<EventId, Event>>,
+    pending: VecDeque<WatchEvent>,
+}
+
+fn make_ticker(interval: Duration) -> Interval {
+    let mut ticker = time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker
+}
+
+fn make_snapshot(events: Vec<Event>) -> HashMap<EventId, Event> {
+    events.into_iter().map(|e| (e.id(), e)).collect()
+}
+
+/// Compares the current events with the previous snapshot, then returns the notifications and the next snapshot.
+/// Added and updated events are notified in the order of `current`, and removed ones follow in the order of `event_id`.
+fn diff_snapshot(
+    mut previous: HashMap<EventId, Event>,
+    current: Vec<Event>,
+) -> (Vec<WatchEvent>, HashMap<EventId, Event>) {
+    let mut notifications = Vec::new();
+    let mut snapshot = HashMap::with_capacity(current.len());
+
+    for event in current {
+        match previous.remove(&event.id()) {
+            None => notifications.push(WatchEvent::EventAdded(event.clone())),
+            Some(old) => {
+                // `old` is looked up by the id of `event`, so the ids always match.
+                let changed_fields = old.changed_fields(&event).unwrap_or_default();
+                if !changed_fields.is_empty() {
+                    notifications.push(WatchEvent::EventUpdated {
+                        event: event.clone(),
+                        changed_fields,
+                    });
+                }
+            }
+        }
+        snapshot.insert(event.id(), event);
+    }
+
+    let mut removed: Vec<Event> = previous.into_values().collect();
+    removed.sort_by_key(|e| e.id());
+    notifications.extend(removed.into_iter().map(WatchEvent::EventRemoved));
+
+    (notifications, snapshot)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use crate::{
+        client::ConnpassClient,
+        query::builder::QueryBuilder,
+        response::{Event, EventField},
+    };
+
+    use super::{diff_snapshot, make_snapshot, WatchEvent, Watcher};
+
+    fn event(id: u32, updated_at: &str, accepted: u32) -> Event {
+        serde_json::from_value(json!({
+            "event_id": id,
+            "title": "Rust.Tokyo",
+            "limit": 30,
+            "accepted": accepted,
+            "waiting": 0,
+            "updated_at": updated_at,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_interval_is_clamped() {
+        let query = QueryBuilder::begin().keyword("Rust").build().unwrap();
+        let watcher = Watcher::new(ConnpassClient::new(), query.clone()).interval(Duration::ZERO);
+        assert_eq!(watcher.interval, Duration::from_secs(1));
+        let watcher = Watcher::new(ConnpassClient::new(), query).interval(Duration::from_secs(600));
+        assert_eq!(watcher.interval, Duration::from_secs(600));
+    }
+
+    #[test]
+    fn test_diff_snapshot_without_changes() {
+        let previous = make_snapshot(vec![event(1, "2021-10-01T10:00:00+09:00", 10)]);
+        let (notifications, snapshot) =
+            diff_snapshot(previous, vec![event(1, "2021-10-01T10:00:00+09:00", 10)]);
+        assert!(notifications.is_empty());
+        assert_eq!(snapshot.len(), 1);
+    }
+
+    #[test]
+    fn test_diff_snapshot_added_updated_removed() {
+        let previous = make_snapshot(vec![
+            event(1, "2021-10-01T10:00:00+09:00", 30),
+            event(2, "2021-10-01T10:00:00+09:00", 10),
+            event(3, "2021-10-01T10:00:00+09:00", 10),
+        ]);
+        let current = vec![
+            event(4, "2021-10-02T10:00:00+09:00", 0),
+            event(1, "2021-10-01T10:00:00+09:00", 29),
+            event(2, "2021-10-02T10:00:00+09:00", 10),
+        ];
+        let (notifications, snapshot) = diff_snapshot(previous, current);
+        assert_eq!(
+            notifications,
+            vec![
+                WatchEvent::EventAdded(event(4, "2021-10-02T10:00:00+09:00", 0)),
+                WatchEvent::EventUpdated {
+                    event: event(1, "2021-10-01T10:00:00+09:00", 29),
+                    changed_fields: vec![EventField::Accepted],
+                },
+                WatchEvent::EventUpdated {
+                    event: event(2, "2021-10-02T10:00:00+09:00", 10),
+                    changed_fields: vec![EventField::UpdatedAt],
+                },
+                WatchEvent::EventRemoved(event(3, "2021-10-01T10:00:00+09:00", 10)),
+            ]
+        );
+        let mut ids: Vec<u32> = snapshot.keys().map(|id| id.get()).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![1, 2, 4]);
+    }
+}